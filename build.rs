@@ -0,0 +1,27 @@
+use std::process::Command;
+
+fn main() {
+    // Prefer the sha given by the build environment, e.g. a docker build argument,
+    // because the .git directory is not always available. A docker build without the
+    // argument still sets it, empty.
+    let git_sha = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|sha| sha.trim().to_string())
+        });
+
+    println!(
+        "cargo:rustc-env=GIT_SHA={}",
+        git_sha.unwrap_or("unknown".to_string())
+    );
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
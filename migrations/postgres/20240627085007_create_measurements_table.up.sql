@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE measurements (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    date_time TIMESTAMPTZ NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+)
@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE impedance (
+    impedance_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    measured_at TIMESTAMPTZ NOT NULL,
+    ohms DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+)
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE users
+ADD COLUMN height_centimeters DOUBLE PRECISION
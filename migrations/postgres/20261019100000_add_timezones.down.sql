@@ -0,0 +1,9 @@
+-- Add down migration script here
+ALTER TABLE impedance
+DROP COLUMN utc_offset_seconds;
+
+ALTER TABLE weight
+DROP COLUMN utc_offset_seconds;
+
+ALTER TABLE users
+DROP COLUMN timezone;
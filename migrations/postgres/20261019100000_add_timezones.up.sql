@@ -0,0 +1,10 @@
+-- Add up migration script here
+ALTER TABLE users
+ADD COLUMN timezone TEXT;
+
+-- TIMESTAMPTZ only keeps the instant, the offset it was measured with is stored apart
+ALTER TABLE weight
+ADD COLUMN utc_offset_seconds INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE impedance
+ADD COLUMN utc_offset_seconds INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX impedance_user_id_measured_at;
+
+DROP INDEX weight_user_id_measured_at;
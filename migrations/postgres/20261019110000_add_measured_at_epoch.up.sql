@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- TIMESTAMPTZ already compares as an instant, only the index for the ranges is missing
+CREATE INDEX weight_user_id_measured_at ON weight (user_id, measured_at);
+
+CREATE INDEX impedance_user_id_measured_at ON impedance (user_id, measured_at);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE webhook_delivery;
+
+DROP TABLE webhook;
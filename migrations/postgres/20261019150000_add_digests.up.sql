@@ -0,0 +1,13 @@
+-- Add up migration script here
+-- Users without a row are never sent a digest
+CREATE TABLE digest (
+    user_id BIGINT PRIMARY KEY,
+    email TEXT NOT NULL,
+    -- weekly or monthly
+    frequency TEXT NOT NULL,
+    last_sent_at TIMESTAMPTZ,
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+)
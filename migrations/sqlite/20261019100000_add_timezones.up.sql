@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- The offset of each measurement is already kept in the RFC 3339 text of measured_at
+ALTER TABLE users
+ADD COLUMN timezone TEXT
@@ -0,0 +1,10 @@
+-- Add down migration script here
+DROP INDEX impedance_user_id_measured_at_epoch_ms;
+
+ALTER TABLE impedance
+DROP COLUMN measured_at_epoch_ms;
+
+DROP INDEX weight_user_id_measured_at_epoch_ms;
+
+ALTER TABLE weight
+DROP COLUMN measured_at_epoch_ms;
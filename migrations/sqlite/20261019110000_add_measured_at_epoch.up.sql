@@ -0,0 +1,18 @@
+-- Add up migration script here
+-- measured_at keeps the offset it was measured with so it does not sort as an instant, the epoch
+-- in milliseconds is what ranges and ordering are computed on
+ALTER TABLE weight
+ADD COLUMN measured_at_epoch_ms INTEGER NOT NULL DEFAULT 0;
+
+UPDATE weight
+SET measured_at_epoch_ms = CAST(ROUND((julianday(measured_at) - 2440587.5) * 86400000) AS INTEGER);
+
+CREATE INDEX weight_user_id_measured_at_epoch_ms ON weight (user_id, measured_at_epoch_ms);
+
+ALTER TABLE impedance
+ADD COLUMN measured_at_epoch_ms INTEGER NOT NULL DEFAULT 0;
+
+UPDATE impedance
+SET measured_at_epoch_ms = CAST(ROUND((julianday(measured_at) - 2440587.5) * 86400000) AS INTEGER);
+
+CREATE INDEX impedance_user_id_measured_at_epoch_ms ON impedance (user_id, measured_at_epoch_ms);
@@ -0,0 +1,34 @@
+-- Add up migration script here
+-- Webhooks without a user receive the events of every user
+CREATE TABLE webhook (
+    webhook_id INTEGER PRIMARY KEY,
+    user_id INTEGER,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    -- Comma separated names of the events it is subscribed to
+    events TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+-- One row per attempt to deliver an event
+CREATE TABLE webhook_delivery (
+    webhook_delivery_id INTEGER PRIMARY KEY,
+    webhook_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    attempted_at TEXT NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    succeeded BOOLEAN NOT NULL,
+    FOREIGN KEY (webhook_id)
+        REFERENCES webhook(webhook_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX webhook_delivery_webhook_id ON webhook_delivery (webhook_id);
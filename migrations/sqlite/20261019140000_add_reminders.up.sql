@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- Users without a row are never reminded
+CREATE TABLE reminder (
+    user_id INTEGER PRIMARY KEY,
+    -- Overrides the days without weighing in of the configuration
+    after_days INTEGER,
+    email TEXT,
+    ntfy_topic TEXT,
+    -- Local times of the user, as HH:MM, the end can be before the start to span midnight
+    quiet_hours_start TEXT,
+    quiet_hours_end TEXT,
+    last_reminded_at TEXT,
+    FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+)
@@ -0,0 +1,12 @@
+#!/bin/sh
+# Downloads the front-end libraries into static/vendor, where they are embedded in the binary and
+# served with hashed names like the rest of the static files. Run it after changing a version and
+# commit the files.
+set -eu
+
+CHART_JS_VERSION=4.5.0
+
+cd "$(dirname "$0")/../static"
+mkdir -p vendor
+curl -fsSL "https://cdn.jsdelivr.net/npm/chart.js@${CHART_JS_VERSION}/dist/chart.umd.js" \
+    -o vendor/chart.umd.js
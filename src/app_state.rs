@@ -1,7 +1,61 @@
-use sqlx::{Pool, Sqlite};
+use chrono_tz::Tz;
+use metrics_exporter_prometheus::PrometheusHandle;
+use std::sync::Arc;
+
+use crate::{events::Events, mqtt::MqttPublisher, repositories::Repository, webhooks::Webhooks};
 
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: Pool<Sqlite>,
+    pub repository: Arc<dyn Repository>,
     pub handlebars: handlebars::Handlebars<'static>,
+    pub mqtt: Option<MqttPublisher>,
+    pub metrics: PrometheusHandle,
+    pub user_gauges: bool,
+    pub timezone: Tz,
+    pub admin_token: Option<String>,
+    /// Changes to the measurements made through the server.
+    pub events: Events,
+    pub webhooks: Webhooks,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State of a server on an empty SQLite database at the URL, for the tests of the routes.
+    pub async fn for_tests(url: &str, admin_token: Option<&str>) -> AppState {
+        use metrics_exporter_prometheus::PrometheusBuilder;
+
+        use crate::{
+            configuration::{DatabaseConfiguration, WebhooksConfiguration},
+            repositories::{schema::SchemaRepository, sqlite::SqliteRepository},
+        };
+
+        let repository = SqliteRepository::connect(&DatabaseConfiguration {
+            url: url.to_string(),
+            max_connections: 1,
+        })
+        .await
+        .unwrap();
+        repository.run_migrations().await.unwrap();
+        let repository: Arc<dyn Repository> = Arc::new(repository);
+
+        AppState {
+            webhooks: Webhooks::new(
+                repository.clone(),
+                &WebhooksConfiguration {
+                    max_attempts: 1,
+                    retry_delay_seconds: 0,
+                    timeout_seconds: 5,
+                },
+            )
+            .unwrap(),
+            repository,
+            handlebars: handlebars::Handlebars::new(),
+            mqtt: None,
+            metrics: PrometheusBuilder::new().build_recorder().handle(),
+            user_gauges: false,
+            timezone: chrono_tz::UTC,
+            admin_token: admin_token.map(str::to_string),
+            events: Events::new(),
+        }
+    }
 }
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+use rust_embed::{EmbeddedFile, RustEmbed};
+use serde_json::Value;
+
+/// Path the files of the static directory are served under.
+pub const PREFIX: &str = "/static";
+
+/// Scripts and stylesheets of the pages, built into the binary so that it runs from any
+/// directory.
+#[derive(RustEmbed)]
+#[folder = "static"]
+struct StaticFiles;
+
+/// Files of the static directory, each one also served under a name with the hash of its content
+/// so that browsers can keep them for good and still get the new ones after an upgrade.
+#[derive(Clone)]
+pub struct Assets {
+    /// Hashed name of each file by its name, relative to the directory with `/` separators.
+    hashed_names: Arc<HashMap<String, String>>,
+    /// The other way around.
+    names: Arc<HashMap<String, String>>,
+    /// Where the files are read from in dev mode, none when they are the embedded ones.
+    dev_dir: Option<PathBuf>,
+}
+
+impl Assets {
+    /// The files embedded in the binary.
+    pub fn embedded() -> Self {
+        let hashed_names: HashMap<String, String> = StaticFiles::iter()
+            .filter_map(|name| {
+                let file = StaticFiles::get(&name)?;
+                let hashed_name = hashed_name(&name, &file.metadata.sha256_hash());
+                Some((name.into_owned(), hashed_name))
+            })
+            .collect();
+        let names = hashed_names
+            .iter()
+            .map(|(name, hashed_name)| (hashed_name.clone(), name.clone()))
+            .collect();
+
+        Assets {
+            hashed_names: Arc::new(hashed_names),
+            names: Arc::new(names),
+            dev_dir: None,
+        }
+    }
+
+    /// The files of the directory, without hashes since they can be edited at any time.
+    pub fn dev(dir: &Path) -> Self {
+        Assets {
+            hashed_names: Arc::default(),
+            names: Arc::default(),
+            dev_dir: Some(dir.to_path_buf()),
+        }
+    }
+
+    pub fn dev_dir(&self) -> Option<&Path> {
+        self.dev_dir.as_deref()
+    }
+
+    /// Embedded file by its plain name.
+    pub fn get(&self, name: &str) -> Option<EmbeddedFile> {
+        StaticFiles::get(name)
+    }
+
+    /// URL of the file with the hash of its content, the plain one when there is no such file.
+    pub fn url(&self, name: &str) -> String {
+        let name = self.hashed_names.get(name).map_or(name, String::as_str);
+        format!("{}/{}", PREFIX, name)
+    }
+
+    /// Name of the file behind the hashed name, none when it is not one.
+    pub fn name(&self, hashed_name: &str) -> Option<&str> {
+        self.names.get(hashed_name).map(String::as_str)
+    }
+}
+
+/// `{{asset "chart.js"}}` writes the URL of the file with its hash.
+impl HelperDef for Assets {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let name = helper
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("asset", 0))?;
+
+        Ok(ScopedJson::Derived(Value::String(self.url(name))))
+    }
+}
+
+/// `chart.js` becomes `chart.0123abcd.js`, with the first hexadecimal digits of the SHA-256 of the
+/// content.
+fn hashed_name(name: &str, sha256: &[u8; 32]) -> String {
+    let hash = hex::encode(&sha256[..4]);
+
+    let file_start = name.rfind('/').map_or(0, |slash| slash + 1);
+    match name[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let (stem, extension) = name.split_at(file_start + dot);
+            format!("{}.{}{}", stem, hash, extension)
+        }
+        _ => format!("{}.{}", name, hash),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_go_before_the_extension() {
+        let sha256 = [0xab; 32];
+
+        assert_eq!(
+            hashed_name("stylesheet.css", &sha256),
+            "stylesheet.abababab.css"
+        );
+        assert_eq!(
+            hashed_name("vendor/lib.min.js", &sha256),
+            "vendor/lib.min.abababab.js"
+        );
+        assert_eq!(
+            hashed_name(".well-known/robots", &sha256),
+            ".well-known/robots.abababab"
+        );
+    }
+
+    #[test]
+    fn urls_of_the_embedded_files_have_their_hash() {
+        let assets = Assets::embedded();
+
+        let url = assets.url("chart.js");
+        let hashed_name = url.strip_prefix("/static/").unwrap();
+
+        assert_ne!(hashed_name, "chart.js");
+        assert_eq!(assets.name(hashed_name), Some("chart.js"));
+        assert!(assets.get("chart.js").is_some());
+        assert_eq!(assets.url("missing.js"), "/static/missing.js");
+        assert_eq!(
+            Assets::dev(Path::new("static")).url("chart.js"),
+            "/static/chart.js"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::Utc;
+
+use crate::{configuration::BackupConfiguration, error::ApiError, repositories::Repository};
+
+const SNAPSHOT_PREFIX: &str = "weight-tracker-";
+const SNAPSHOT_EXTENSION: &str = "db";
+
+/// Writes a consistent snapshot of the database into the directory while it keeps serving requests.
+pub async fn snapshot(repository: &dyn Repository, directory: &Path) -> Result<PathBuf, ApiError> {
+    tokio::fs::create_dir_all(directory)
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    let path = directory.join(format!(
+        "{}{}.{}",
+        SNAPSHOT_PREFIX,
+        Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+        SNAPSHOT_EXTENSION
+    ));
+
+    repository.snapshot(&path).await?;
+
+    Ok(path)
+}
+
+/// Deletes the oldest snapshots in the directory so that only `retention` of them are kept.
+pub async fn prune(directory: &Path, retention: usize) -> Result<Vec<PathBuf>, ApiError> {
+    let mut snapshots = vec![];
+    let mut entries = tokio::fs::read_dir(directory)
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+    {
+        let path = entry.path();
+        if is_snapshot(&path) {
+            snapshots.push(path);
+        }
+    }
+
+    // Names contain the timestamp so sorting them sorts the snapshots from oldest to newest
+    snapshots.sort();
+    let expired: Vec<PathBuf> = snapshots
+        .drain(..snapshots.len().saturating_sub(retention))
+        .collect();
+
+    for path in expired.iter() {
+        tokio::fs::remove_file(path)
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    Ok(expired)
+}
+
+/// Takes a snapshot every configured interval, the first one right away.
+pub fn schedule(repository: Arc<dyn Repository>, configuration: BackupConfiguration) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(configuration.interval_hours * 60 * 60));
+
+        loop {
+            interval.tick().await;
+
+            match snapshot(&*repository, &configuration.directory).await {
+                Ok(path) => tracing::info!(path = %path.display(), "Database backup created"),
+                Err(error) => {
+                    tracing::error!(?error, "Failed to create database backup");
+                    continue;
+                }
+            }
+
+            if let Err(error) = prune(&configuration.directory, configuration.retention).await {
+                tracing::error!(?error, "Failed to delete old database backups");
+            }
+        }
+    });
+}
+
+fn is_snapshot(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    name.starts_with(SNAPSHOT_PREFIX) && path.extension().is_some_and(|e| e == SNAPSHOT_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn prune_keeps_newest_snapshots() -> Result<(), ApiError> {
+        let directory =
+            std::env::temp_dir().join(format!("weight-tracker-prune-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let names = [
+            "weight-tracker-20250101T000000.000Z.db",
+            "weight-tracker-20250102T000000.000Z.db",
+            "weight-tracker-20250103T000000.000Z.db",
+            "unrelated.db",
+        ];
+        for name in names {
+            std::fs::write(directory.join(name), "").unwrap();
+        }
+
+        let expired = prune(&directory, 2).await?;
+
+        assert_eq!(expired, vec![directory.join(names[0])]);
+        assert!(directory.join(names[1]).exists());
+        assert!(directory.join(names[2]).exists());
+        assert!(directory.join(names[3]).exists());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+        Ok(())
+    }
+}
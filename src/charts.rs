@@ -0,0 +1,288 @@
+use std::{
+    fmt::Write,
+    sync::{Arc, OnceLock},
+};
+
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use resvg::{tiny_skia, usvg};
+
+use crate::{
+    domain::weight::{self, Weight},
+    error::ApiError,
+};
+
+/// Space around the plot for the labels of the axes.
+const MARGIN_LEFT: f64 = 44.0;
+const MARGIN_RIGHT: f64 = 12.0;
+const MARGIN_TOP: f64 = 10.0;
+const MARGIN_BOTTOM: f64 = 22.0;
+/// Horizontal lines of the grid, the lowest and highest included.
+const GRID_LINES: u32 = 5;
+const WEIGHT_COLOR: &str = "#36a2eb";
+const TREND_COLOR: &str = "#999999";
+const GOAL_COLOR: &str = "#4bc07a";
+const LABEL_COLOR: &str = "#666666";
+const GRID_COLOR: &str = "#e5e5e5";
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// DejaVu Sans, built into the binary so that the labels of the rasterized charts are drawn on
+/// servers without any font installed.
+const FONT: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+/// Fonts of the rasterized charts, loaded the first time they are needed.
+static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+
+/// Line chart of weights with their least squares trend and the goal, drawn as SVG so that it can
+/// be served as is or rasterized.
+pub struct WeightChart<'a> {
+    /// Oldest first.
+    pub weights: &'a [Weight],
+    pub goal: Option<f64>,
+    /// Range of the horizontal axis.
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    /// Timezone of the days labelling the horizontal axis.
+    pub timezone: Tz,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WeightChart<'_> {
+    pub fn svg(&self) -> String {
+        let (width, height) = (f64::from(self.width), f64::from(self.height));
+        let (left, right) = (MARGIN_LEFT, width - MARGIN_RIGHT);
+        let (top, bottom) = (MARGIN_TOP, height - MARGIN_BOTTOM);
+        let points: Vec<(f64, f64)> = self
+            .weights
+            .iter()
+            .map(|weight| {
+                (
+                    weight.measured_at.timestamp() as f64,
+                    (&weight.kilograms).into(),
+                )
+            })
+            .collect();
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="11"><rect width="100%" height="100%" fill="white"/>"#
+        );
+
+        let (first, last) = (self.start.timestamp() as f64, self.end.timestamp() as f64);
+        let x = |timestamp: f64| {
+            if last > first {
+                left + (timestamp - first) / (last - first) * (right - left)
+            } else {
+                (left + right) / 2.0
+            }
+        };
+
+        for (timestamp, anchor) in [
+            (first, "start"),
+            ((first + last) / 2.0, "middle"),
+            (last, "end"),
+        ] {
+            let day = DateTime::from_timestamp(timestamp as i64, 0)
+                .map(|instant| {
+                    instant
+                        .with_timezone(&self.timezone)
+                        .format("%Y-%m-%d")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            let _ = write!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="{anchor}" fill="{LABEL_COLOR}">{day}</text>"#,
+                x(timestamp),
+                height - 6.0
+            );
+        }
+
+        if points.is_empty() {
+            let _ = write!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="{LABEL_COLOR}">No weights</text></svg>"#,
+                (left + right) / 2.0,
+                (top + bottom) / 2.0
+            );
+            return svg;
+        }
+
+        let kilograms = || {
+            points
+                .iter()
+                .map(|(_, kilograms)| *kilograms)
+                .chain(self.goal)
+        };
+        let mut lowest = kilograms().fold(f64::INFINITY, f64::min);
+        let mut highest = kilograms().fold(f64::NEG_INFINITY, f64::max);
+        // Keeps flat series in the middle instead of dividing by zero
+        if highest - lowest < 1.0 {
+            lowest -= 0.5;
+            highest += 0.5;
+        }
+        let y = |kilograms: f64| top + (highest - kilograms) / (highest - lowest) * (bottom - top);
+
+        for line in 0..GRID_LINES {
+            let kilograms =
+                lowest + (highest - lowest) * f64::from(line) / f64::from(GRID_LINES - 1);
+            let _ = write!(
+                svg,
+                r#"<line x1="{left}" y1="{y:.1}" x2="{right}" y2="{y:.1}" stroke="{GRID_COLOR}"/><text x="{:.1}" y="{:.1}" text-anchor="end" fill="{LABEL_COLOR}">{kilograms:.1}</text>"#,
+                left - 4.0,
+                y(kilograms) + 4.0,
+                y = y(kilograms)
+            );
+        }
+
+        if let Some(goal) = self.goal {
+            let _ = write!(
+                svg,
+                r#"<line x1="{left}" y1="{y:.1}" x2="{right}" y2="{y:.1}" stroke="{GOAL_COLOR}" stroke-width="1.5" stroke-dasharray="6 4"/>"#,
+                y = y(goal)
+            );
+        }
+
+        // The trend of the statistics and the digests, through the average of the weights
+        let slope = weight::slope(self.weights, &self.timezone) / SECONDS_PER_DAY;
+        if let (true, Some(oldest), Some(newest)) =
+            (slope.is_finite(), points.first(), points.last())
+        {
+            let n = points.len() as f64;
+            let mean_timestamp = points.iter().map(|(timestamp, _)| timestamp).sum::<f64>() / n;
+            let mean_kilograms = points.iter().map(|(_, kilograms)| kilograms).sum::<f64>() / n;
+            let trend = |timestamp: f64| mean_kilograms + slope * (timestamp - mean_timestamp);
+            let _ = write!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{TREND_COLOR}" stroke-width="1.5" stroke-dasharray="3 3"/>"#,
+                x(oldest.0),
+                y(trend(oldest.0)),
+                x(newest.0),
+                y(trend(newest.0))
+            );
+        }
+
+        if points.len() > 1 {
+            let line: Vec<String> = points
+                .iter()
+                .map(|(timestamp, kilograms)| format!("{:.1},{:.1}", x(*timestamp), y(*kilograms)))
+                .collect();
+            let _ = write!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{WEIGHT_COLOR}" stroke-width="2" stroke-linejoin="round"/>"#,
+                line.join(" ")
+            );
+        }
+        for (timestamp, kilograms) in &points {
+            let _ = write!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="2.5" fill="{WEIGHT_COLOR}"/>"#,
+                x(*timestamp),
+                y(*kilograms)
+            );
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    pub fn png(&self) -> Result<Vec<u8>, ApiError> {
+        self.rasterize(1)?
+            .encode_png()
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    /// Red, green and blue bytes of each pixel, row by row, of the chart rasterized `scale` times
+    /// larger, for documents that embed raw images.
+    pub fn rgb(&self, scale: u32) -> Result<Vec<u8>, ApiError> {
+        // The background is opaque so the colors are not premultiplied by any alpha
+        Ok(self
+            .rasterize(scale)?
+            .data()
+            .chunks_exact(4)
+            .flat_map(|pixel| &pixel[..3])
+            .copied()
+            .collect())
+    }
+
+    fn rasterize(&self, scale: u32) -> Result<tiny_skia::Pixmap, ApiError> {
+        let options = usvg::Options {
+            fontdb: FONTS.get_or_init(fonts).clone(),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_str(&self.svg(), &options)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        let mut pixmap = tiny_skia::Pixmap::new(self.width * scale, self.height * scale)
+            .ok_or_else(|| ApiError::Unexpected("Invalid chart size".into()))?;
+
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(scale as f32, scale as f32),
+            &mut pixmap.as_mut(),
+        );
+
+        Ok(pixmap)
+    }
+}
+
+fn fonts() -> Arc<usvg::fontdb::Database> {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_font_data(FONT.to_vec());
+    fonts.set_sans_serif_family(FONT_FAMILY);
+
+    Arc::new(fonts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        user::UserId,
+        weight::{Kilograms, WeightId},
+    };
+
+    use super::*;
+
+    fn weight(measured_at: &str, kilograms: f64) -> Weight {
+        Weight {
+            weight_id: WeightId::new(1),
+            user_id: UserId::new(1),
+            measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+            kilograms: Kilograms::new(kilograms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn charts_are_rasterized_to_the_requested_size() {
+        let weights = [
+            weight("2026-10-12T08:00:00+02:00", 80.0),
+            weight("2026-10-15T08:00:00+02:00", 79.0),
+            weight("2026-10-18T08:00:00+02:00", 78.5),
+        ];
+        let chart = WeightChart {
+            weights: &weights,
+            goal: Some(75.0),
+            start: DateTime::parse_from_rfc3339("2026-10-12T00:00:00+02:00").unwrap(),
+            end: DateTime::parse_from_rfc3339("2026-10-18T23:59:59+02:00").unwrap(),
+            timezone: Tz::Europe__Madrid,
+            width: 320,
+            height: 120,
+        };
+
+        let svg = chart.svg();
+        let png = chart.png().unwrap();
+
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("stroke-dasharray").count(), 2);
+        assert!(svg.contains(">2026-10-12</text>"));
+        assert!(svg.contains(">2026-10-18</text>"));
+        assert_eq!(&png[1..4], b"PNG");
+        // Width and height of the IHDR chunk
+        assert_eq!(&png[16..24], &[0, 0, 1, 64, 0, 0, 0, 120]);
+        // The labels do not depend on the fonts of the system
+        assert!(fonts().faces().any(|face| face
+            .families
+            .iter()
+            .any(|(family, _)| family == FONT_FAMILY)));
+    }
+}
@@ -0,0 +1,622 @@
+use std::{
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use axum::{middleware, Router};
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::{stream::BoxStream, TryStreamExt};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    signal,
+};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+
+use crate::{
+    app_state::AppState,
+    assets::{self, Assets},
+    backup,
+    configuration::{Configuration, Overrides},
+    digests::Digests,
+    domain::{
+        impedance::Ohms,
+        user::{Centimeters, UserId, UserName},
+        weight::Kilograms,
+    },
+    error::ApiError,
+    events::Events,
+    export,
+    mailer::Mailer,
+    mqtt::MqttPublisher,
+    reminders::Reminders,
+    repositories::{self, Repository},
+    routes, telemetry, templates,
+    webhooks::Webhooks,
+};
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    pub overrides: Overrides,
+    /// Defaults to `serve` when no command is given.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Runs the migrations and starts the web server.
+    Serve,
+    /// Runs the pending migrations and exits.
+    Migrate,
+    /// Manages the users.
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Imports measurements from a CSV file with the same columns as the CSV export.
+    Import {
+        #[arg(long)]
+        user_id: i64,
+        #[arg(long, value_enum)]
+        measurement: Measurement,
+        file: PathBuf,
+    },
+    /// Exports the measurements of a user as CSV.
+    Export {
+        #[arg(long)]
+        user_id: i64,
+        #[arg(long, value_enum)]
+        measurement: Measurement,
+        /// RFC 3339 date time, defaults to the first measurement.
+        #[arg(long)]
+        start_date: Option<String>,
+        /// RFC 3339 date time, defaults to now.
+        #[arg(long)]
+        end_date: Option<String>,
+        /// File to write to, defaults to the standard output.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Takes a snapshot of the database and prunes the old ones.
+    Backup {
+        /// Defaults to the directory of the backup configuration.
+        #[arg(long)]
+        directory: Option<PathBuf>,
+    },
+    /// Verifies the configuration, the database, the migrations and the templates.
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum UsersCommand {
+    List,
+    Add {
+        name: String,
+        #[arg(long)]
+        height_centimeters: Option<f64>,
+        /// IANA timezone name, defaults to the application timezone.
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Weight to reach, goal reached webhooks are sent when it is crossed.
+        #[arg(long)]
+        goal_kilograms: Option<f64>,
+    },
+    Delete {
+        id: i64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Measurement {
+    Weights,
+    Impedances,
+}
+
+pub async fn run(command: Command, configuration: Configuration) -> Result<(), ApiError> {
+    match command {
+        Command::Serve => serve(configuration).await,
+        Command::Migrate => migrate(&configuration).await,
+        Command::Users(command) => users(command, &configuration).await,
+        Command::Import {
+            user_id,
+            measurement,
+            file,
+        } => import(&configuration, UserId::new(user_id), measurement, &file).await,
+        Command::Export {
+            user_id,
+            measurement,
+            start_date,
+            end_date,
+            output,
+        } => {
+            let start_date = match start_date {
+                Some(d) => parse_date_time("start_date", &d)?,
+                None => DateTime::<Utc>::UNIX_EPOCH.fixed_offset(),
+            };
+            let end_date = match end_date {
+                Some(d) => parse_date_time("end_date", &d)?,
+                None => Utc::now().fixed_offset(),
+            };
+            if start_date > end_date {
+                return Err(ApiError::StartDateGreaterThanEndDate);
+            }
+
+            let csv = export_csv(
+                &configuration,
+                UserId::new(user_id),
+                measurement,
+                &start_date,
+                &end_date,
+            )
+            .await?;
+
+            let output: Pin<Box<dyn AsyncWrite>> = match output {
+                Some(path) => Box::pin(
+                    tokio::fs::File::create(path)
+                        .await
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                ),
+                None => Box::pin(tokio::io::stdout()),
+            };
+            write_stream(csv, output).await
+        }
+        Command::Backup { directory } => take_backup(&configuration, directory).await,
+        Command::Check => check(&configuration).await,
+    }
+}
+
+async fn connect(configuration: &Configuration) -> Result<Arc<dyn Repository>, ApiError> {
+    repositories::connect(&configuration.database)
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+async fn connect_and_migrate(
+    configuration: &Configuration,
+) -> Result<Arc<dyn Repository>, ApiError> {
+    let repository = connect(configuration).await?;
+    repository.run_migrations().await?;
+
+    Ok(repository)
+}
+
+fn parse_date_time(field: &'static str, value: &str) -> Result<DateTime<FixedOffset>, ApiError> {
+    DateTime::<FixedOffset>::parse_from_rfc3339(value).map_err(|_| ApiError::InvalidDateTime(field))
+}
+
+async fn serve(configuration: Configuration) -> Result<(), ApiError> {
+    let metrics = telemetry::install_recorder().map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    let repository = connect_and_migrate(&configuration).await?;
+
+    let mut handlebars = handlebars::Handlebars::new();
+
+    let application = &configuration.application;
+    templates::register(
+        &mut handlebars,
+        application
+            .dev_mode
+            .then_some(application.templates_dir.as_path()),
+    )
+    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    let assets = if application.dev_mode {
+        tracing::info!("Dev mode, templates and static files are read from their directories");
+        Assets::dev(&application.static_dir)
+    } else {
+        Assets::embedded()
+    };
+    handlebars.register_helper("asset", Box::new(assets.clone()));
+
+    let mqtt = configuration
+        .mqtt
+        .as_ref()
+        .map(|mqtt| MqttPublisher::connect(mqtt, configuration.application.timezone));
+
+    if let Some(mqtt) = &mqtt {
+        let mqtt = mqtt.clone();
+        let repository = repository.clone();
+        tokio::spawn(async move {
+            if let Err(error) = mqtt.publish_all_users(&*repository).await {
+                tracing::warn!(?error, "Failed to publish users to MQTT");
+            }
+        });
+    }
+
+    if let Some(backup) = configuration.backup {
+        backup::schedule(repository.clone(), backup);
+    }
+
+    let events = Events::new();
+    let webhooks = Webhooks::new(repository.clone(), &configuration.webhooks)?;
+    webhooks.dispatch(&events);
+    webhooks.resume();
+
+    let mailer = configuration.smtp.as_ref().map(Mailer::new).transpose()?;
+
+    if let Some(reminders) = configuration.reminders {
+        Reminders::new(
+            repository.clone(),
+            reminders,
+            mailer.clone(),
+            webhooks.clone(),
+            configuration.application.timezone,
+        )?
+        .schedule();
+    }
+
+    // Validation makes sure SMTP is configured along with the digests
+    if let (Some(digests), Some(mailer)) = (configuration.digests, mailer) {
+        Digests::new(
+            repository.clone(),
+            digests,
+            mailer,
+            handlebars.clone(),
+            configuration.application.timezone,
+        )
+        .schedule();
+    }
+
+    let app_state = AppState {
+        repository,
+        handlebars,
+        mqtt,
+        metrics,
+        user_gauges: configuration.metrics.user_gauges,
+        timezone: configuration.application.timezone,
+        admin_token: configuration.admin.token,
+        events: events.clone(),
+        webhooks,
+    };
+
+    let router = Router::new()
+        .merge(routes::index(app_state.clone()))
+        .merge(routes::chart(app_state.clone()))
+        .merge(routes::table(app_state.clone()))
+        .merge(routes::metrics(app_state.clone()))
+        .merge(routes::health(app_state.clone()))
+        .nest("/api/v1", routes::api_v1(app_state.clone()))
+        .nest("/api", routes::api(app_state.clone()))
+        .merge(routes::docs())
+        .nest_service(assets::PREFIX, routes::assets(assets))
+        .fallback_service(routes::not_found(app_state.clone()))
+        .layer(middleware::from_fn(telemetry::track_http))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(telemetry::trace_layer())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+    tracing::info!(
+        "Listening on {}:{}",
+        configuration.application.listen_address,
+        configuration.application.listen_port
+    );
+
+    let listener = tokio::net::TcpListener::bind((
+        configuration.application.listen_address.as_str(),
+        configuration.application.listen_port,
+    ))
+    .await
+    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            // Event streams never end by themselves
+            events.close();
+        })
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+async fn migrate(configuration: &Configuration) -> Result<(), ApiError> {
+    let repository = connect(configuration).await?;
+    let pending = repository.find_pending_migrations().await?;
+
+    repository.run_migrations().await?;
+
+    println!("Applied {} migrations", pending.len());
+    Ok(())
+}
+
+async fn users(command: UsersCommand, configuration: &Configuration) -> Result<(), ApiError> {
+    let repository = connect_and_migrate(configuration).await?;
+
+    match command {
+        UsersCommand::List => {
+            for user in repository.find_users().await? {
+                let id: i64 = (&user.id).into();
+                let name: String = user.name.into();
+                let height = user
+                    .height
+                    .as_ref()
+                    .map(|height| f64::from(height).to_string())
+                    .unwrap_or_default();
+                let timezone = user
+                    .timezone
+                    .map(|timezone| timezone.name())
+                    .unwrap_or_default();
+                let goal = user
+                    .goal
+                    .as_ref()
+                    .map(|goal| f64::from(goal).to_string())
+                    .unwrap_or_default();
+                println!("{}\t{}\t{}\t{}\t{}", id, name, height, timezone, goal);
+            }
+        }
+        UsersCommand::Add {
+            name,
+            height_centimeters,
+            timezone,
+            goal_kilograms,
+        } => {
+            let height = height_centimeters.map(Centimeters::new).transpose()?;
+            let goal = goal_kilograms.map(Kilograms::new).transpose()?;
+            repository
+                .insert_user(
+                    &UserName::new(name),
+                    height.as_ref(),
+                    timezone.as_ref(),
+                    goal.as_ref(),
+                )
+                .await?;
+        }
+        UsersCommand::Delete { id } => {
+            repository.delete_user(&UserId::new(id)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads `measured_at` and the value of every row of a CSV with a header, in any column order.
+fn parse_csv(
+    content: &str,
+    value_column: &str,
+) -> Result<Vec<(DateTime<FixedOffset>, f64)>, ApiError> {
+    let invalid = |line: usize, reason: &str| {
+        ApiError::Unexpected(format!("Invalid CSV at line {}: {}", line, reason).into())
+    };
+
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or_else(|| invalid(1, "missing header"))?;
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    let position = |name: &str| {
+        columns
+            .iter()
+            .position(|column| *column == name)
+            .ok_or_else(|| invalid(1, &format!("missing column {}", name)))
+    };
+    let measured_at_column = position("measured_at")?;
+    let value_column = position(value_column)?;
+
+    lines
+        .map(|(index, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |column: usize| {
+                fields
+                    .get(column)
+                    .copied()
+                    .ok_or_else(|| invalid(index + 1, "missing field"))
+            };
+            let measured_at = parse_date_time("measured_at", field(measured_at_column)?)
+                .map_err(|_| invalid(index + 1, "invalid measured_at"))?;
+            let value = field(value_column)?
+                .parse::<f64>()
+                .map_err(|_| invalid(index + 1, "invalid number"))?;
+            Ok((measured_at, value))
+        })
+        .collect()
+}
+
+async fn import(
+    configuration: &Configuration,
+    user_id: UserId,
+    measurement: Measurement,
+    file: &Path,
+) -> Result<(), ApiError> {
+    let content = tokio::fs::read_to_string(file)
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    let repository = connect_and_migrate(configuration).await?;
+
+    let user_id = repository
+        .find_user(&user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id;
+
+    // Every row is validated first and all of them are inserted in a single transaction, so
+    // neither a bad file nor a database error leaves a partial import
+    match measurement {
+        Measurement::Weights => {
+            let rows = parse_csv(&content, "kilograms")?
+                .into_iter()
+                .map(|(measured_at, kilograms)| Ok((measured_at, Kilograms::new(kilograms)?)))
+                .collect::<Result<Vec<_>, ApiError>>()?;
+            repository.insert_weights(&user_id, &rows).await?;
+            println!("Imported {} weights", rows.len());
+        }
+        Measurement::Impedances => {
+            let rows = parse_csv(&content, "ohms")?
+                .into_iter()
+                .map(|(measured_at, ohms)| Ok((measured_at, Ohms::new(ohms)?)))
+                .collect::<Result<Vec<_>, ApiError>>()?;
+            repository.insert_impedances(&user_id, &rows).await?;
+            println!("Imported {} impedances", rows.len());
+        }
+    }
+
+    Ok(())
+}
+
+async fn export_csv(
+    configuration: &Configuration,
+    user_id: UserId,
+    measurement: Measurement,
+    start_date: &DateTime<FixedOffset>,
+    end_date: &DateTime<FixedOffset>,
+) -> Result<BoxStream<'static, Result<String, ApiError>>, ApiError> {
+    let repository = connect_and_migrate(configuration).await?;
+
+    // A mistyped identifier would otherwise be an empty export
+    let user_id = repository
+        .find_user(&user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id;
+
+    Ok(match measurement {
+        Measurement::Weights => export::csv_stream(
+            repository.stream_weights_between_dates(&user_id, start_date, end_date),
+        ),
+        Measurement::Impedances => export::csv_stream(
+            repository.stream_impedances_between_dates(&user_id, start_date, end_date),
+        ),
+    })
+}
+
+/// Writes the chunks as they come, ending with a new line.
+async fn write_stream(
+    mut chunks: BoxStream<'static, Result<String, ApiError>>,
+    mut output: Pin<Box<dyn AsyncWrite>>,
+) -> Result<(), ApiError> {
+    while let Some(chunk) = chunks.try_next().await? {
+        output
+            .write_all(chunk.as_bytes())
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    output
+        .write_all(b"\n")
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    output
+        .flush()
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+async fn take_backup(
+    configuration: &Configuration,
+    directory: Option<PathBuf>,
+) -> Result<(), ApiError> {
+    let backup = configuration.backup.as_ref();
+    let directory = directory
+        .or_else(|| backup.map(|backup| backup.directory.clone()))
+        .ok_or_else(|| ApiError::Unexpected("No backup directory given nor configured".into()))?;
+
+    let repository = connect(configuration).await?;
+    let path = backup::snapshot(&*repository, &directory).await?;
+    println!("{}", path.display());
+
+    if let Some(backup) = backup {
+        for path in backup::prune(&directory, backup.retention).await? {
+            tracing::info!(path = %path.display(), "Deleted expired backup");
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the result of every check and fails if any of them did.
+async fn check(configuration: &Configuration) -> Result<(), ApiError> {
+    // Reaching this point means the configuration was loaded and validated
+    println!("configuration: ok");
+    let mut healthy = true;
+
+    match connect(configuration).await {
+        Ok(repository) => {
+            match repository.ping().await {
+                Ok(()) => println!("database: ok"),
+                Err(error) => {
+                    healthy = false;
+                    println!("database: {}", error);
+                }
+            }
+            match repository.find_pending_migrations().await {
+                Ok(pending) if pending.is_empty() => println!("migrations: ok"),
+                Ok(pending) => {
+                    healthy = false;
+                    println!("migrations: {} pending", pending.len());
+                }
+                Err(error) => {
+                    healthy = false;
+                    println!("migrations: {}", error);
+                }
+            }
+        }
+        Err(error) => {
+            healthy = false;
+            println!("database: {}", error);
+        }
+    }
+
+    let mut handlebars = handlebars::Handlebars::new();
+    let application = &configuration.application;
+    let dev_dir = application
+        .dev_mode
+        .then_some(application.templates_dir.as_path());
+    match templates::register(&mut handlebars, dev_dir) {
+        Ok(()) => println!("templates: ok"),
+        Err(error) => {
+            healthy = false;
+            println!("templates: {}", error);
+        }
+    }
+
+    if !healthy {
+        return Err(ApiError::Unexpected("Some checks failed".into()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_csv;
+
+    #[test]
+    fn parse_csv_reads_columns_by_header() {
+        let content = "kilograms,weight_id,measured_at\n80.5,1,2024-01-01T08:00:00+01:00\n\n";
+
+        let rows = parse_csv(content, "kilograms").unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0.to_rfc3339(), "2024-01-01T08:00:00+01:00");
+        assert_eq!(rows[0].1, 80.5);
+    }
+
+    #[test]
+    fn parse_csv_fails_without_value_column() {
+        let content = "measured_at,ohms\n2024-01-01T08:00:00+01:00,500";
+
+        assert!(parse_csv(content, "kilograms").is_err());
+    }
+}
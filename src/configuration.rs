@@ -1,28 +1,791 @@
+use std::{fmt, path::PathBuf};
+
+use chrono_tz::Tz;
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+
+/// Settings of the service. They are layered, from lowest to highest precedence: built-in
+/// defaults, the TOML configuration file, environment variables and command line flags.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Configuration {
     pub application: ApplicationConfiguration,
     pub database: DatabaseConfiguration,
+    pub mqtt: Option<MqttConfiguration>,
+    pub metrics: MetricsConfiguration,
+    pub logging: LoggingConfiguration,
+    pub admin: AdminConfiguration,
+    pub backup: Option<BackupConfiguration>,
+    pub webhooks: WebhooksConfiguration,
+    pub smtp: Option<SmtpConfiguration>,
+    pub reminders: Option<RemindersConfiguration>,
+    pub digests: Option<DigestsConfiguration>,
 }
 
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ApplicationConfiguration {
     pub listen_address: String,
-    pub listen_port: String,
+    pub listen_port: u16,
+    /// Pages and their files are read from the directories instead of the copies embedded in the
+    /// binary, templates again whenever they are rendered, so that they can be edited while the
+    /// server runs.
+    pub dev_mode: bool,
+    /// Only read in dev mode.
+    pub templates_dir: PathBuf,
+    pub static_dir: PathBuf,
+    /// Timezone used to display dates and to bucket weights by day, defaults to the system one.
+    pub timezone: Tz,
 }
 
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct DatabaseConfiguration {
+    /// `sqlite:` or `postgres:` URL, the scheme selects the storage backend.
     pub url: String,
+    pub max_connections: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DatabaseConfiguration {
+    pub fn backend(&self) -> Option<DatabaseBackend> {
+        match self.url.split_once(':')?.0 {
+            "sqlite" => Some(DatabaseBackend::Sqlite),
+            "postgres" | "postgresql" => Some(DatabaseBackend::Postgres),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MqttConfiguration {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub topic_prefix: String,
+    pub discovery_prefix: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfiguration {
+    /// Exposes the latest weight and trend of every user, they are opt-in because they contain personal data.
+    pub user_gauges: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfiguration {
+    /// Filter in the `tracing_subscriber::EnvFilter` syntax, e.g. `info` or `weight_tracker=debug`.
+    pub level: String,
+    pub format: LogFormat,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfiguration {
+    /// Bearer token required by the `/api/admin` endpoints, they are disabled when it is not set.
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfiguration {
+    pub directory: PathBuf,
+    pub interval_hours: u64,
+    /// Number of snapshots kept in the directory, the oldest ones are deleted.
+    pub retention: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhooksConfiguration {
+    /// Attempts to deliver each event, including the first one.
+    pub max_attempts: u32,
+    /// Wait before the first retry, it doubles with each of the next ones.
+    pub retry_delay_seconds: u64,
+    pub timeout_seconds: u64,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfiguration {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender of every email, e.g. `Weight Tracker <weight@example.com>`.
+    pub from: String,
+    pub tls: SmtpTls,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain text, only meant for a local relay.
+    None,
+    #[default]
+    Starttls,
+    /// Implicit TLS, usually on port 465.
+    Tls,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemindersConfiguration {
+    /// Days without weighing in before a user is reminded, each user can change them.
+    pub after_days: u32,
+    pub check_interval_minutes: u64,
+    /// Server the ntfy topics of the users are published to.
+    pub ntfy_url: String,
+    /// Bearer token of the ntfy server, when it requires one.
+    pub ntfy_token: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DigestsConfiguration {
+    /// Local hour of the day, from 0 to 23, digests are sent at once their period has ended.
+    pub send_hour: u32,
+    pub check_interval_minutes: u64,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl Default for ApplicationConfiguration {
+    fn default() -> Self {
+        ApplicationConfiguration {
+            listen_address: "127.0.0.1".to_string(),
+            listen_port: 3000,
+            dev_mode: false,
+            templates_dir: PathBuf::from("templates"),
+            static_dir: PathBuf::from("static"),
+            timezone: iana_time_zone::get_timezone()
+                .ok()
+                .and_then(|name| name.parse().ok())
+                .unwrap_or(Tz::UTC),
+        }
+    }
+}
+
+impl Default for DatabaseConfiguration {
+    fn default() -> Self {
+        DatabaseConfiguration {
+            url: String::new(),
+            max_connections: 5,
+        }
+    }
+}
+
+impl Default for MqttConfiguration {
+    fn default() -> Self {
+        MqttConfiguration {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "weight-tracker".to_string(),
+            username: None,
+            password: None,
+            topic_prefix: "weight-tracker".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+impl Default for BackupConfiguration {
+    fn default() -> Self {
+        BackupConfiguration {
+            directory: PathBuf::from("backups"),
+            interval_hours: 24,
+            retention: 7,
+        }
+    }
+}
+
+impl Default for WebhooksConfiguration {
+    fn default() -> Self {
+        WebhooksConfiguration {
+            max_attempts: 5,
+            retry_delay_seconds: 10,
+            timeout_seconds: 10,
+        }
+    }
+}
+
+impl Default for SmtpConfiguration {
+    fn default() -> Self {
+        SmtpConfiguration {
+            host: "localhost".to_string(),
+            port: 587,
+            username: None,
+            password: None,
+            from: "Weight Tracker <weight-tracker@localhost>".to_string(),
+            tls: SmtpTls::Starttls,
+        }
+    }
+}
+
+impl Default for RemindersConfiguration {
+    fn default() -> Self {
+        RemindersConfiguration {
+            after_days: 2,
+            check_interval_minutes: 60,
+            ntfy_url: "https://ntfy.sh".to_string(),
+            ntfy_token: None,
+        }
+    }
 }
 
-pub fn get_configuration() -> Configuration {
-    let listen_address: String = std::env::var("LISTEN_ADDRESS").unwrap_or("127.0.0.1".to_string());
-    let listen_port = std::env::var("LISTEN_PORT").unwrap_or("3000".to_string());
+impl Default for DigestsConfiguration {
+    fn default() -> Self {
+        DigestsConfiguration {
+            send_hour: 8,
+            check_interval_minutes: 60,
+        }
+    }
+}
+
+impl Default for LoggingConfiguration {
+    fn default() -> Self {
+        LoggingConfiguration {
+            level: "info".to_string(),
+            format: LogFormat::Pretty,
+        }
+    }
+}
+
+/// Command line flags, each of them can also be set with the environment variable next to it.
+#[derive(Args, Default)]
+pub struct Overrides {
+    /// TOML configuration file
+    #[arg(long = "config", env = "CONFIG_FILE")]
+    pub config_file: Option<PathBuf>,
+
+    #[arg(long, env = "LISTEN_ADDRESS")]
+    pub listen_address: Option<String>,
+
+    #[arg(long, env = "LISTEN_PORT")]
+    pub listen_port: Option<u16>,
+
+    #[arg(long, env = "DEV_MODE")]
+    pub dev_mode: Option<bool>,
+
+    #[arg(long, env = "TEMPLATES_DIR")]
+    pub templates_dir: Option<PathBuf>,
+
+    #[arg(long, env = "STATIC_DIR")]
+    pub static_dir: Option<PathBuf>,
+
+    /// IANA timezone name, e.g. Europe/Madrid
+    #[arg(long, env = "TIMEZONE")]
+    pub timezone: Option<Tz>,
+
+    #[arg(long, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    #[arg(long, env = "DATABASE_MAX_CONNECTIONS")]
+    pub database_max_connections: Option<u32>,
+
+    /// Enables publishing to MQTT when set
+    #[arg(long, env = "MQTT_HOST")]
+    pub mqtt_host: Option<String>,
+
+    #[arg(long, env = "MQTT_PORT")]
+    pub mqtt_port: Option<u16>,
+
+    #[arg(long, env = "MQTT_CLIENT_ID")]
+    pub mqtt_client_id: Option<String>,
+
+    #[arg(long, env = "MQTT_USERNAME")]
+    pub mqtt_username: Option<String>,
+
+    #[arg(long, env = "MQTT_PASSWORD", hide_env_values = true)]
+    pub mqtt_password: Option<String>,
+
+    #[arg(long, env = "MQTT_TOPIC_PREFIX")]
+    pub mqtt_topic_prefix: Option<String>,
+
+    #[arg(long, env = "MQTT_DISCOVERY_PREFIX")]
+    pub mqtt_discovery_prefix: Option<String>,
+
+    #[arg(long, env = "METRICS_USER_GAUGES")]
+    pub metrics_user_gauges: Option<bool>,
+
+    #[arg(long, env = "LOG_LEVEL")]
+    pub log_level: Option<String>,
+
+    #[arg(long, env = "LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+
+    #[arg(long, env = "ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<String>,
+
+    /// Enables scheduled backups when set
+    #[arg(long, env = "BACKUP_DIRECTORY")]
+    pub backup_directory: Option<PathBuf>,
+
+    #[arg(long, env = "BACKUP_INTERVAL_HOURS")]
+    pub backup_interval_hours: Option<u64>,
+
+    #[arg(long, env = "BACKUP_RETENTION")]
+    pub backup_retention: Option<usize>,
+
+    #[arg(long, env = "WEBHOOKS_MAX_ATTEMPTS")]
+    pub webhooks_max_attempts: Option<u32>,
+
+    #[arg(long, env = "WEBHOOKS_RETRY_DELAY_SECONDS")]
+    pub webhooks_retry_delay_seconds: Option<u64>,
+
+    #[arg(long, env = "WEBHOOKS_TIMEOUT_SECONDS")]
+    pub webhooks_timeout_seconds: Option<u64>,
+
+    /// Enables sending emails when set
+    #[arg(long, env = "SMTP_HOST")]
+    pub smtp_host: Option<String>,
+
+    #[arg(long, env = "SMTP_PORT")]
+    pub smtp_port: Option<u16>,
+
+    #[arg(long, env = "SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+
+    #[arg(long, env = "SMTP_PASSWORD", hide_env_values = true)]
+    pub smtp_password: Option<String>,
+
+    #[arg(long, env = "SMTP_FROM")]
+    pub smtp_from: Option<String>,
+
+    #[arg(long, env = "SMTP_TLS")]
+    pub smtp_tls: Option<SmtpTls>,
+
+    /// Enables weigh-in reminders when set
+    #[arg(long, env = "REMINDERS_AFTER_DAYS")]
+    pub reminders_after_days: Option<u32>,
+
+    #[arg(long, env = "REMINDERS_CHECK_INTERVAL_MINUTES")]
+    pub reminders_check_interval_minutes: Option<u64>,
+
+    #[arg(long, env = "REMINDERS_NTFY_URL")]
+    pub reminders_ntfy_url: Option<String>,
+
+    #[arg(long, env = "REMINDERS_NTFY_TOKEN", hide_env_values = true)]
+    pub reminders_ntfy_token: Option<String>,
+
+    /// Enables the email digests when set, requires SMTP
+    #[arg(long, env = "DIGESTS_SEND_HOUR")]
+    pub digests_send_hour: Option<u32>,
+
+    #[arg(long, env = "DIGESTS_CHECK_INTERVAL_MINUTES")]
+    pub digests_check_interval_minutes: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ConfigurationError {
+    ReadFile(PathBuf, std::io::Error),
+    ParseFile(PathBuf, toml::de::Error),
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for ConfigurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadFile(path, error) => write!(f, "cannot read {}: {}", path.display(), error),
+            Self::ParseFile(path, error) => {
+                write!(f, "cannot parse {}: {}", path.display(), error)
+            }
+            Self::Invalid(setting, reason) => write!(f, "{} {}", setting, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigurationError {}
+
+pub fn get_configuration(overrides: &Overrides) -> Result<Configuration, ConfigurationError> {
+    let mut configuration = match &overrides.config_file {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| ConfigurationError::ReadFile(path.clone(), e))?;
+            toml::from_str(&contents).map_err(|e| ConfigurationError::ParseFile(path.clone(), e))?
+        }
+        None => Configuration::default(),
+    };
+
+    configuration.apply(overrides);
+    configuration.validate()?;
+
+    Ok(configuration)
+}
+
+impl Configuration {
+    fn apply(&mut self, overrides: &Overrides) {
+        let application = &mut self.application;
+        set(&mut application.listen_address, &overrides.listen_address);
+        set(&mut application.listen_port, &overrides.listen_port);
+        set(&mut application.dev_mode, &overrides.dev_mode);
+        set(&mut application.templates_dir, &overrides.templates_dir);
+        set(&mut application.static_dir, &overrides.static_dir);
+        set(&mut application.timezone, &overrides.timezone);
+
+        set(&mut self.database.url, &overrides.database_url);
+        set(
+            &mut self.database.max_connections,
+            &overrides.database_max_connections,
+        );
+
+        if self.mqtt.is_none() && overrides.mqtt_host.is_some() {
+            self.mqtt = Some(MqttConfiguration::default());
+        }
+        if let Some(mqtt) = &mut self.mqtt {
+            set(&mut mqtt.host, &overrides.mqtt_host);
+            set(&mut mqtt.port, &overrides.mqtt_port);
+            set(&mut mqtt.client_id, &overrides.mqtt_client_id);
+            set_option(&mut mqtt.username, &overrides.mqtt_username);
+            set_option(&mut mqtt.password, &overrides.mqtt_password);
+            set(&mut mqtt.topic_prefix, &overrides.mqtt_topic_prefix);
+            set(&mut mqtt.discovery_prefix, &overrides.mqtt_discovery_prefix);
+        }
+
+        set(
+            &mut self.metrics.user_gauges,
+            &overrides.metrics_user_gauges,
+        );
+
+        set(&mut self.logging.level, &overrides.log_level);
+        set(&mut self.logging.format, &overrides.log_format);
+
+        set_option(&mut self.admin.token, &overrides.admin_token);
+
+        if self.backup.is_none() && overrides.backup_directory.is_some() {
+            self.backup = Some(BackupConfiguration::default());
+        }
+        if let Some(backup) = &mut self.backup {
+            set(&mut backup.directory, &overrides.backup_directory);
+            set(&mut backup.interval_hours, &overrides.backup_interval_hours);
+            set(&mut backup.retention, &overrides.backup_retention);
+        }
+
+        let webhooks = &mut self.webhooks;
+        set(&mut webhooks.max_attempts, &overrides.webhooks_max_attempts);
+        set(
+            &mut webhooks.retry_delay_seconds,
+            &overrides.webhooks_retry_delay_seconds,
+        );
+        set(
+            &mut webhooks.timeout_seconds,
+            &overrides.webhooks_timeout_seconds,
+        );
+
+        if self.smtp.is_none() && overrides.smtp_host.is_some() {
+            self.smtp = Some(SmtpConfiguration::default());
+        }
+        if let Some(smtp) = &mut self.smtp {
+            set(&mut smtp.host, &overrides.smtp_host);
+            set(&mut smtp.port, &overrides.smtp_port);
+            set_option(&mut smtp.username, &overrides.smtp_username);
+            set_option(&mut smtp.password, &overrides.smtp_password);
+            set(&mut smtp.from, &overrides.smtp_from);
+            set(&mut smtp.tls, &overrides.smtp_tls);
+        }
+
+        if self.reminders.is_none() && overrides.reminders_after_days.is_some() {
+            self.reminders = Some(RemindersConfiguration::default());
+        }
+        if let Some(reminders) = &mut self.reminders {
+            set(&mut reminders.after_days, &overrides.reminders_after_days);
+            set(
+                &mut reminders.check_interval_minutes,
+                &overrides.reminders_check_interval_minutes,
+            );
+            set(&mut reminders.ntfy_url, &overrides.reminders_ntfy_url);
+            set_option(&mut reminders.ntfy_token, &overrides.reminders_ntfy_token);
+        }
+
+        if self.digests.is_none() && overrides.digests_send_hour.is_some() {
+            self.digests = Some(DigestsConfiguration::default());
+        }
+        if let Some(digests) = &mut self.digests {
+            set(&mut digests.send_hour, &overrides.digests_send_hour);
+            set(
+                &mut digests.check_interval_minutes,
+                &overrides.digests_check_interval_minutes,
+            );
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigurationError> {
+        if self.database.url.is_empty() {
+            return Err(ConfigurationError::Invalid(
+                "database.url",
+                "must be set".to_string(),
+            ));
+        }
+
+        if self.database.backend().is_none() {
+            return Err(ConfigurationError::Invalid(
+                "database.url",
+                "must start with sqlite: or postgres:".to_string(),
+            ));
+        }
+
+        if self.database.max_connections == 0 {
+            return Err(ConfigurationError::Invalid(
+                "database.max_connections",
+                "must be greater than zero".to_string(),
+            ));
+        }
+
+        for (setting, dir) in [
+            ("application.templates_dir", &self.application.templates_dir),
+            ("application.static_dir", &self.application.static_dir),
+        ] {
+            if self.application.dev_mode && !dir.is_dir() {
+                return Err(ConfigurationError::Invalid(
+                    setting,
+                    format!("{} is not a directory", dir.display()),
+                ));
+            }
+        }
+
+        if let Some(mqtt) = &self.mqtt {
+            if mqtt.host.is_empty() {
+                return Err(ConfigurationError::Invalid(
+                    "mqtt.host",
+                    "must not be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(backup) = &self.backup {
+            if self.database.backend() != Some(DatabaseBackend::Sqlite) {
+                return Err(ConfigurationError::Invalid(
+                    "backup",
+                    "snapshots are only supported with SQLite".to_string(),
+                ));
+            }
+            if backup.interval_hours == 0 {
+                return Err(ConfigurationError::Invalid(
+                    "backup.interval_hours",
+                    "must be greater than zero".to_string(),
+                ));
+            }
+            if backup.retention == 0 {
+                return Err(ConfigurationError::Invalid(
+                    "backup.retention",
+                    "must be greater than zero".to_string(),
+                ));
+            }
+        }
+
+        if self.webhooks.max_attempts == 0 {
+            return Err(ConfigurationError::Invalid(
+                "webhooks.max_attempts",
+                "must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.webhooks.timeout_seconds == 0 {
+            return Err(ConfigurationError::Invalid(
+                "webhooks.timeout_seconds",
+                "must be greater than zero".to_string(),
+            ));
+        }
+
+        if let Some(smtp) = &self.smtp {
+            if smtp.host.is_empty() {
+                return Err(ConfigurationError::Invalid(
+                    "smtp.host",
+                    "must not be empty".to_string(),
+                ));
+            }
+            if smtp.from.parse::<lettre::message::Mailbox>().is_err() {
+                return Err(ConfigurationError::Invalid(
+                    "smtp.from",
+                    "must be an email address, optionally with a name".to_string(),
+                ));
+            }
+        }
+
+        if let Some(reminders) = &self.reminders {
+            if reminders.after_days == 0 {
+                return Err(ConfigurationError::Invalid(
+                    "reminders.after_days",
+                    "must be greater than zero".to_string(),
+                ));
+            }
+            if reminders.check_interval_minutes == 0 {
+                return Err(ConfigurationError::Invalid(
+                    "reminders.check_interval_minutes",
+                    "must be greater than zero".to_string(),
+                ));
+            }
+            if reqwest::Url::parse(&reminders.ntfy_url).is_err() {
+                return Err(ConfigurationError::Invalid(
+                    "reminders.ntfy_url",
+                    "must be a URL".to_string(),
+                ));
+            }
+        }
+
+        if let Some(digests) = &self.digests {
+            if self.smtp.is_none() {
+                return Err(ConfigurationError::Invalid(
+                    "digests",
+                    "requires the smtp section to send them".to_string(),
+                ));
+            }
+            if digests.send_hour > 23 {
+                return Err(ConfigurationError::Invalid(
+                    "digests.send_hour",
+                    "must be between 0 and 23".to_string(),
+                ));
+            }
+            if digests.check_interval_minutes == 0 {
+                return Err(ConfigurationError::Invalid(
+                    "digests.check_interval_minutes",
+                    "must be greater than zero".to_string(),
+                ));
+            }
+        }
+
+        if let Err(error) = tracing_subscriber::EnvFilter::try_new(&self.logging.level) {
+            return Err(ConfigurationError::Invalid(
+                "logging.level",
+                error.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn set<T: Clone>(setting: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *setting = value.clone();
+    }
+}
+
+fn set_option<T: Clone>(setting: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        *setting = value.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Configuration {
+        toml::from_str(contents).expect("configuration should be valid")
+    }
+
+    #[test]
+    fn file_settings_are_overridden() {
+        let mut configuration = parse(
+            r#"
+            [application]
+            listen_port = 8080
+
+            [database]
+            url = "sqlite://file.db"
+            max_connections = 2
+            "#,
+        );
+
+        configuration.apply(&Overrides {
+            database_url: Some("sqlite://override.db".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(configuration.application.listen_port, 8080);
+        assert_eq!(configuration.database.url, "sqlite://override.db");
+        assert_eq!(configuration.database.max_connections, 2);
+        assert!(configuration.mqtt.is_none());
+    }
+
+    #[test]
+    fn mqtt_is_enabled_by_host_override() {
+        let mut configuration = Configuration::default();
+
+        configuration.apply(&Overrides {
+            mqtt_host: Some("broker".to_string()),
+            ..Default::default()
+        });
+
+        let mqtt = configuration.mqtt.expect("mqtt should be enabled");
+        assert_eq!(mqtt.host, "broker");
+        assert_eq!(mqtt.port, 1883);
+    }
+
+    #[test]
+    fn missing_database_url_is_invalid() {
+        let configuration = Configuration::default();
+
+        match configuration.validate() {
+            Err(ConfigurationError::Invalid(setting, _)) => assert_eq!(setting, "database.url"),
+            _ => panic!("configuration without database url should be invalid"),
+        }
+    }
+
+    #[test]
+    fn template_and_static_directories_are_only_needed_in_dev_mode() {
+        let mut configuration = parse(
+            r#"
+            [application]
+            templates_dir = "missing"
+
+            [database]
+            url = "sqlite://file.db"
+            "#,
+        );
+        assert!(configuration.validate().is_ok());
+
+        configuration.apply(&Overrides {
+            dev_mode: Some(true),
+            ..Default::default()
+        });
+        match configuration.validate() {
+            Err(ConfigurationError::Invalid(setting, _)) => {
+                assert_eq!(setting, "application.templates_dir")
+            }
+            _ => panic!("dev mode without templates should be invalid"),
+        }
+    }
+
+    #[test]
+    fn backend_is_selected_by_url_scheme() {
+        let backend = |url: &str| {
+            DatabaseConfiguration {
+                url: url.to_string(),
+                ..Default::default()
+            }
+            .backend()
+        };
+
+        assert_eq!(backend("sqlite://file.db"), Some(DatabaseBackend::Sqlite));
+        assert_eq!(
+            backend("postgres://user@localhost/weight"),
+            Some(DatabaseBackend::Postgres)
+        );
+        assert_eq!(backend("mysql://localhost/weight"), None);
+    }
 
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    #[test]
+    fn unknown_settings_are_rejected() {
+        let result = toml::from_str::<Configuration>("[application]\nlisten_prot = 8080");
 
-    Configuration {
-        application: ApplicationConfiguration {
-            listen_address,
-            listen_port,
-        },
-        database: DatabaseConfiguration { url: database_url },
+        assert!(result.is_err(), "typos in settings should not be ignored");
     }
 }
@@ -0,0 +1,343 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+
+use crate::{
+    charts::WeightChart,
+    configuration::DigestsConfiguration,
+    domain::digest::{Digest, DigestFrequency, DigestSettings},
+    error::ApiError,
+    mailer::Mailer,
+    repositories::Repository,
+    scheduler,
+};
+
+const CHART_WIDTH: u32 = 560;
+const CHART_HEIGHT: u32 = 200;
+
+/// Emails each user that opted in a summary of its weights once every week or month.
+#[derive(Clone)]
+pub struct Digests {
+    repository: Arc<dyn Repository>,
+    configuration: DigestsConfiguration,
+    mailer: Mailer,
+    handlebars: handlebars::Handlebars<'static>,
+    timezone: Tz,
+}
+
+impl Digests {
+    /// `timezone` is the one of the users without their own, for the days of their periods.
+    pub fn new(
+        repository: Arc<dyn Repository>,
+        configuration: DigestsConfiguration,
+        mailer: Mailer,
+        handlebars: handlebars::Handlebars<'static>,
+        timezone: Tz,
+    ) -> Self {
+        Digests {
+            repository,
+            configuration,
+            mailer,
+            handlebars,
+            timezone,
+        }
+    }
+
+    /// Checks which digests are due every configured interval.
+    pub fn schedule(self) {
+        let period = Duration::from_secs(self.configuration.check_interval_minutes * 60);
+        let digests = Arc::new(self);
+
+        scheduler::every(period, "Failed to check the digests", move |now| {
+            let digests = digests.clone();
+            async move { digests.send_all(&now).await }
+        });
+    }
+
+    /// Sends the digests due at the time, the ones that fail are logged and left for the next
+    /// check.
+    pub async fn send_all(&self, now: &DateTime<Utc>) -> Result<(), ApiError> {
+        for settings in self.repository.find_all_digest_settings().await? {
+            if let Err(error) = self.send(&settings, now).await {
+                let user_id: i64 = (&settings.user_id).into();
+                tracing::warn!(?error, user_id, "Failed to send the digest");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the digest of the user was due and sent.
+    async fn send(&self, settings: &DigestSettings, now: &DateTime<Utc>) -> Result<bool, ApiError> {
+        let Some(user) = self.repository.find_user(&settings.user_id).await? else {
+            return Ok(false);
+        };
+        let timezone = user.timezone_or(self.timezone);
+        let Some(period) = settings.due(now, timezone, self.configuration.send_hour) else {
+            return Ok(false);
+        };
+
+        let (start_date, end_date) = period.bounds(&timezone);
+        let weights = self
+            .repository
+            .find_weights_between_dates(&user.id, &start_date, &end_date)
+            .await?;
+        let (previous_start_date, previous_end_date) = period.previous().bounds(&timezone);
+        let previous_weights = self
+            .repository
+            .find_weights_between_dates(&user.id, &previous_start_date, &previous_end_date)
+            .await?;
+
+        let sent = match Digest::new(
+            period,
+            &weights,
+            &previous_weights,
+            user.goal.as_ref(),
+            &timezone,
+        ) {
+            Some(digest) => {
+                let chart = WeightChart {
+                    weights: &weights,
+                    goal: user.goal.as_ref().map(f64::from),
+                    start: start_date,
+                    end: end_date,
+                    timezone,
+                    width: CHART_WIDTH,
+                    height: CHART_HEIGHT,
+                }
+                .png()?;
+                let name: String = user.name.into();
+                let (text, html) = self.render(&name, &digest)?;
+
+                self.mailer
+                    .send_html(
+                        &settings.email,
+                        &format!("Your weight digest: {}", period.title()),
+                        text,
+                        html,
+                        vec![("chart", chart)],
+                    )
+                    .await?;
+                true
+            }
+            // Reminders are what tell about not weighing in, the period is skipped all the same
+            None => false,
+        };
+
+        self.repository
+            .update_digest_sent_at(&settings.user_id, now)
+            .await?;
+
+        Ok(sent)
+    }
+
+    /// Plain text and HTML bodies of the email.
+    fn render(&self, name: &str, digest: &Digest) -> Result<(String, String), ApiError> {
+        let kilograms = |value: f64| format!("{:.1} kg", value);
+        let difference = |value: f64| format!("{:+.1} kg", value);
+        let period = match digest.period.frequency {
+            DigestFrequency::Weekly => "week",
+            DigestFrequency::Monthly => "month",
+        };
+
+        let mut lines = vec![
+            format!("Hi {}, this is your weight digest.", name),
+            digest.period.title(),
+            String::new(),
+            format!("Weigh-ins: {}", digest.weigh_ins),
+            format!("Average: {}", kilograms(digest.average)),
+            format!("Min weight: {}", kilograms(digest.minimum)),
+            format!("Max weight: {}", kilograms(digest.maximum)),
+            format!("Last weight: {}", kilograms(digest.latest)),
+        ];
+        if let Some(change) = digest.change {
+            lines.push(format!(
+                "Change since the previous {}: {}",
+                period,
+                difference(change)
+            ));
+        }
+        if let Some(trend) = digest.trend {
+            lines.push(format!("Trend: {}/week", difference(trend)));
+        }
+        if let Some(goal) = &digest.goal {
+            lines.push(format!("Goal: {}", kilograms(goal.goal)));
+            lines.push(format!("Left to the goal: {}", kilograms(goal.remaining)));
+            lines.push(format!(
+                "Closer to the goal this {}: {}",
+                period,
+                difference(goal.closer)
+            ));
+        }
+
+        let data = json!({
+            "title": digest.period.title(),
+            "name": name,
+            "period": period,
+            "weigh_ins": digest.weigh_ins,
+            "average": kilograms(digest.average),
+            "minimum": kilograms(digest.minimum),
+            "maximum": kilograms(digest.maximum),
+            "latest": kilograms(digest.latest),
+            "change": digest.change.map(difference),
+            "trend": digest.trend.map(|trend| format!("{}/week", difference(trend))),
+            "goal": digest.goal.as_ref().map(|goal| kilograms(goal.goal)),
+            "remaining": digest.goal.as_ref().map(|goal| kilograms(goal.remaining)),
+            "closer": digest.goal.as_ref().map(|goal| difference(goal.closer)),
+        });
+        let html = self
+            .handlebars
+            .render("digest", &data)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok((lines.join("\n"), html))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use crate::{
+        configuration::{DatabaseConfiguration, SmtpConfiguration, SmtpTls},
+        domain::{
+            user::{UserId, UserName},
+            weight::Kilograms,
+        },
+        repositories::sqlite::SqliteRepository,
+        templates,
+    };
+
+    use super::*;
+
+    type Received = Arc<Mutex<Vec<String>>>;
+
+    /// Local SMTP sink accepting every message, keeping the data of each one.
+    async fn smtp_sink() -> (Received, u16) {
+        let received = Received::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let messages = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let messages = messages.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    let mut data: Option<Vec<String>> = None;
+
+                    writer.write_all(b"220 sink\r\n").await.unwrap();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let reply: &[u8] = match &mut data {
+                            Some(message) if line == "." => {
+                                messages.lock().unwrap().push(message.join("\n"));
+                                data = None;
+                                b"250 queued\r\n"
+                            }
+                            Some(message) => {
+                                message.push(line);
+                                continue;
+                            }
+                            None if line.starts_with("DATA") => {
+                                data = Some(Vec::new());
+                                b"354 go ahead\r\n"
+                            }
+                            None if line.starts_with("QUIT") => b"221 bye\r\n",
+                            None => b"250 ok\r\n",
+                        };
+                        writer.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        (received, port)
+    }
+
+    #[tokio::test]
+    async fn weekly_digests_are_emailed_once_the_week_has_ended() {
+        let repository: Arc<dyn Repository> = Arc::new(
+            SqliteRepository::connect(&DatabaseConfiguration {
+                url: "sqlite::memory:".to_string(),
+                max_connections: 1,
+            })
+            .await
+            .unwrap(),
+        );
+        repository.run_migrations().await.unwrap();
+        repository
+            .insert_user(
+                &UserName::new("Ana".to_string()),
+                None,
+                None,
+                Some(&Kilograms::new(75.0).unwrap()),
+            )
+            .await
+            .unwrap();
+        let user_id = UserId::new(1);
+        for (measured_at, kilograms) in [
+            ("2026-10-08T08:00:00+02:00", 81.0),
+            ("2026-10-12T08:00:00+02:00", 80.0),
+            ("2026-10-15T08:00:00+02:00", 79.0),
+            ("2026-10-18T08:00:00+02:00", 78.5),
+        ] {
+            repository
+                .insert_weight(
+                    &user_id,
+                    &DateTime::parse_from_rfc3339(measured_at).unwrap(),
+                    &Kilograms::new(kilograms).unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        repository
+            .save_digest_settings(&DigestSettings {
+                user_id: UserId::new(1),
+                email: "ana@example.com".parse().unwrap(),
+                frequency: DigestFrequency::Weekly,
+                last_sent_at: None,
+            })
+            .await
+            .unwrap();
+
+        let (received, port) = smtp_sink().await;
+        let mailer = Mailer::new(&SmtpConfiguration {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: SmtpTls::None,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut handlebars = handlebars::Handlebars::new();
+        templates::register(&mut handlebars, None).unwrap();
+        let digests = Digests::new(
+            repository,
+            DigestsConfiguration::default(),
+            mailer,
+            handlebars,
+            Tz::Europe__Madrid,
+        );
+        let at = |value: &str| DateTime::parse_from_rfc3339(value).unwrap().to_utc();
+
+        digests.send_all(&at("2026-10-19T07:00:00Z")).await.unwrap();
+        digests.send_all(&at("2026-10-20T07:00:00Z")).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let message = &received[0];
+        assert!(message.contains("Subject: Your weight digest: Week of 12 Oct 2026"));
+        assert!(message.contains("Average: 79.2 kg"));
+        assert!(message.contains("Change since the previous week: -1.8 kg"));
+        assert!(message.contains("Left to the goal: 3.5 kg"));
+        assert!(message.contains("Content-Type: image/png"));
+        assert!(message.contains("Content-ID: <chart>"));
+    }
+}
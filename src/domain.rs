@@ -1,3 +1,9 @@
+pub mod calendar;
+pub mod digest;
 pub mod impedance;
+pub mod page;
+pub mod reminder;
+pub mod report;
 pub mod user;
+pub mod webhook;
 pub mod weight;
@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Day of the instant in the timezone, whatever the offset it was measured with.
+pub fn day(instant: &DateTime<FixedOffset>, timezone: &Tz) -> NaiveDate {
+    instant.with_timezone(timezone).date_naive()
+}
+
+/// First instant of the day in the timezone.
+pub fn start_of_day(day: NaiveDate, timezone: &Tz) -> DateTime<FixedOffset> {
+    // Where a DST change skips midnight the day starts at the first hour that exists
+    (0..24)
+        .find_map(|hour| {
+            timezone
+                .from_local_datetime(&day.and_time(NaiveTime::from_hms_opt(hour, 0, 0)?))
+                .earliest()
+        })
+        .unwrap_or_else(|| timezone.from_utc_datetime(&day.and_time(NaiveTime::MIN)))
+        .fixed_offset()
+}
+
+/// Last millisecond of the day in the timezone.
+pub fn end_of_day(day: NaiveDate, timezone: &Tz) -> DateTime<FixedOffset> {
+    start_of_day(day + Duration::days(1), timezone) - Duration::milliseconds(1)
+}
+
+/// First and last instant of the month in the timezone, none if the month does not exist.
+pub fn month_bounds(
+    year: i32,
+    month: u32,
+    timezone: &Tz,
+) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last_day = first_day.checked_add_months(Months::new(1))? - Duration::days(1);
+
+    Some((
+        start_of_day(first_day, timezone),
+        end_of_day(last_day, timezone),
+    ))
+}
+
+/// Days with more than one of the instants together with how many there are, oldest first.
+pub fn duplicate_days(
+    instants: &[DateTime<FixedOffset>],
+    timezone: &Tz,
+) -> Vec<(NaiveDate, usize)> {
+    let mut counts: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for instant in instants {
+        *counts.entry(day(instant, timezone)).or_default() += 1;
+    }
+
+    counts.into_iter().filter(|(_, count)| *count > 1).collect()
+}
+
+/// Years of the instants, newest first, formatted as `2024`.
+pub fn years(instants: &[DateTime<FixedOffset>], timezone: &Tz) -> Vec<String> {
+    let years: BTreeSet<i32> = instants
+        .iter()
+        .map(|instant| instant.with_timezone(timezone).year())
+        .collect();
+
+    years
+        .into_iter()
+        .rev()
+        .map(|year| format!("{:04}", year))
+        .collect()
+}
+
+/// Months of the year with instants, newest first, formatted as `01`.
+pub fn months(instants: &[DateTime<FixedOffset>], timezone: &Tz, year: i32) -> Vec<String> {
+    let months: BTreeSet<u32> = instants
+        .iter()
+        .map(|instant| instant.with_timezone(timezone))
+        .filter(|date_time| date_time.year() == year)
+        .map(|date_time| date_time.month())
+        .collect();
+
+    months
+        .into_iter()
+        .rev()
+        .map(|month| format!("{:02}", month))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instant(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).expect("instant should be valid")
+    }
+
+    #[test]
+    fn days_are_taken_from_the_timezone_not_the_offset() {
+        // Measured while travelling, it is still the 2nd back home in Madrid
+        let instants = [
+            instant("2024-01-01T20:00:00-05:00"),
+            instant("2024-01-02T08:00:00+01:00"),
+        ];
+
+        let duplicates = duplicate_days(&instants, &chrono_tz::Europe::Madrid);
+
+        assert_eq!(
+            duplicates,
+            vec![(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), 2)]
+        );
+        assert!(duplicate_days(&instants, &chrono_tz::America::New_York).is_empty());
+    }
+
+    #[test]
+    fn month_bounds_follow_the_timezone() {
+        let (start, end) = month_bounds(2024, 3, &chrono_tz::Europe::Madrid).unwrap();
+
+        assert_eq!(start.to_rfc3339(), "2024-03-01T00:00:00+01:00");
+        assert_eq!(end.to_rfc3339(), "2024-03-31T23:59:59.999+02:00");
+        assert!(month_bounds(2024, 13, &chrono_tz::Europe::Madrid).is_none());
+    }
+
+    #[test]
+    fn years_and_months_are_newest_first() {
+        let instants = [
+            instant("2023-12-31T23:30:00+00:00"),
+            instant("2024-02-10T08:00:00+00:00"),
+        ];
+        let timezone = chrono_tz::Europe::Madrid;
+
+        assert_eq!(years(&instants, &timezone), vec!["2024"]);
+        assert_eq!(months(&instants, &timezone, 2024), vec!["02", "01"]);
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, Utc};
+use chrono_tz::Tz;
+use lettre::Address;
+
+use crate::error::ApiError;
+
+use super::{
+    calendar,
+    user::UserId,
+    weight::{self, Kilograms, Weight},
+};
+
+/// Where and how often a user wants its digest, users without them are never sent one.
+pub struct DigestSettings {
+    pub user_id: UserId,
+    pub email: Address,
+    pub frequency: DigestFrequency,
+    pub last_sent_at: Option<DateTime<Utc>>,
+}
+
+impl DigestSettings {
+    /// Period whose digest is due now, none when it is not. A period is sent once it has ended and
+    /// the hour of the day it is sent at has come in the timezone of the user.
+    pub fn due(&self, now: &DateTime<Utc>, timezone: Tz, send_hour: u32) -> Option<Period> {
+        let period = self.frequency.last_period(now, timezone);
+        let sent_from = calendar::start_of_day(period.last_day + Duration::days(1), &timezone)
+            + Duration::hours(send_hour.into());
+
+        if *now < sent_from {
+            return None;
+        }
+        if self
+            .last_sent_at
+            .is_some_and(|sent_at| sent_at >= sent_from)
+        {
+            return None;
+        }
+
+        Some(period)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DigestFrequency {
+    /// From Monday to Sunday.
+    Weekly,
+    /// Calendar months.
+    Monthly,
+}
+
+impl DigestFrequency {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DigestFrequency::Weekly => "weekly",
+            DigestFrequency::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<DigestFrequency, ApiError> {
+        match name {
+            "weekly" => Ok(DigestFrequency::Weekly),
+            "monthly" => Ok(DigestFrequency::Monthly),
+            _ => Err(ApiError::InvalidDigestFrequency),
+        }
+    }
+
+    /// Period the day belongs to.
+    pub fn period_of(&self, day: NaiveDate) -> Period {
+        let first_day = match self {
+            DigestFrequency::Weekly => {
+                day - Duration::days(day.weekday().num_days_from_monday().into())
+            }
+            DigestFrequency::Monthly => day.with_day(1).unwrap_or(day),
+        };
+        let last_day = match self {
+            DigestFrequency::Weekly => first_day + Duration::days(6),
+            DigestFrequency::Monthly => first_day
+                .checked_add_months(Months::new(1))
+                .map_or(first_day, |next| next - Duration::days(1)),
+        };
+
+        Period {
+            frequency: *self,
+            first_day,
+            last_day,
+        }
+    }
+
+    /// Latest period that has already ended in the timezone.
+    pub fn last_period(&self, now: &DateTime<Utc>, timezone: Tz) -> Period {
+        let today = now.with_timezone(&timezone).date_naive();
+        self.period_of(today).previous()
+    }
+}
+
+/// Days a digest is about, both included.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Period {
+    pub frequency: DigestFrequency,
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+}
+
+impl Period {
+    pub fn previous(&self) -> Period {
+        self.frequency.period_of(self.first_day - Duration::days(1))
+    }
+
+    /// First and last instant of the period in the timezone.
+    pub fn bounds(&self, timezone: &Tz) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        (
+            calendar::start_of_day(self.first_day, timezone),
+            calendar::end_of_day(self.last_day, timezone),
+        )
+    }
+
+    /// `Week of 12 Oct 2026` or `October 2026`.
+    pub fn title(&self) -> String {
+        match self.frequency {
+            DigestFrequency::Weekly => self.first_day.format("Week of %-d %b %Y").to_string(),
+            DigestFrequency::Monthly => self.first_day.format("%B %Y").to_string(),
+        }
+    }
+}
+
+/// Summary of the weights of a user over a period.
+pub struct Digest {
+    pub period: Period,
+    pub weigh_ins: usize,
+    pub average: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+    /// Difference with the average of the previous period, none when it had no weights.
+    pub change: Option<f64>,
+    /// Kilograms per week, none when the weights are not spread over more than one day.
+    pub trend: Option<f64>,
+    pub latest: f64,
+    pub goal: Option<GoalProgress>,
+}
+
+pub struct GoalProgress {
+    pub goal: f64,
+    /// Kilograms between the latest weight and the goal.
+    pub remaining: f64,
+    /// How much closer to the goal the latest weight is than the first one of the period, negative
+    /// when it moved away.
+    pub closer: f64,
+}
+
+impl Digest {
+    /// Digest of the weights of the period, oldest first, none when there are none.
+    pub fn new(
+        period: Period,
+        weights: &[Weight],
+        previous_weights: &[Weight],
+        goal: Option<&Kilograms>,
+        timezone: &Tz,
+    ) -> Option<Digest> {
+        let (first, latest) = (weights.first()?, weights.last()?);
+        let first: f64 = (&first.kilograms).into();
+        let latest: f64 = (&latest.kilograms).into();
+        let average = average(weights)?;
+
+        Some(Digest {
+            period,
+            weigh_ins: weights.len(),
+            average,
+            minimum: kilograms(weights).fold(f64::INFINITY, f64::min),
+            maximum: kilograms(weights).fold(f64::NEG_INFINITY, f64::max),
+            change: average_change(average, previous_weights),
+            trend: Some(weight::slope(weights, timezone) * 7.0).filter(|trend| trend.is_finite()),
+            latest,
+            goal: goal.map(|goal| {
+                let goal: f64 = goal.into();
+                GoalProgress {
+                    goal,
+                    remaining: (latest - goal).abs(),
+                    closer: (first - goal).abs() - (latest - goal).abs(),
+                }
+            }),
+        })
+    }
+}
+
+fn kilograms(weights: &[Weight]) -> impl Iterator<Item = f64> + '_ {
+    weights.iter().map(|weight| (&weight.kilograms).into())
+}
+
+fn average(weights: &[Weight]) -> Option<f64> {
+    if weights.is_empty() {
+        return None;
+    }
+    Some(kilograms(weights).sum::<f64>() / weights.len() as f64)
+}
+
+fn average_change(average_now: f64, previous_weights: &[Weight]) -> Option<f64> {
+    average(previous_weights).map(|previous| average_now - previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::weight::WeightId;
+
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn weight(measured_at: &str, kilograms: f64) -> Weight {
+        Weight {
+            weight_id: WeightId::new(1),
+            user_id: UserId::new(1),
+            measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+            kilograms: Kilograms::new(kilograms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn periods_are_weeks_from_monday_or_calendar_months() {
+        let week = DigestFrequency::Weekly.period_of(day("2026-10-15"));
+        let month = DigestFrequency::Monthly.period_of(day("2026-03-15"));
+
+        assert_eq!(
+            (week.first_day, week.last_day),
+            (day("2026-10-12"), day("2026-10-18"))
+        );
+        assert_eq!(
+            (week.previous().first_day, week.previous().last_day),
+            (day("2026-10-05"), day("2026-10-11"))
+        );
+        assert_eq!(
+            (month.first_day, month.last_day),
+            (day("2026-03-01"), day("2026-03-31"))
+        );
+        assert_eq!(month.previous().last_day, day("2026-02-28"));
+        assert_eq!(month.title(), "March 2026");
+    }
+
+    #[test]
+    fn digests_are_due_once_after_the_period_at_the_local_send_hour() {
+        let mut settings = DigestSettings {
+            user_id: UserId::new(1),
+            email: "ana@example.com".parse().unwrap(),
+            frequency: DigestFrequency::Weekly,
+            last_sent_at: None,
+        };
+        let at = |value: &str| DateTime::parse_from_rfc3339(value).unwrap().to_utc();
+
+        // 07:30 on Monday in Madrid, before the send hour
+        assert_eq!(
+            settings.due(&at("2026-10-19T05:30:00Z"), Tz::Europe__Madrid, 8),
+            None
+        );
+        let due = settings
+            .due(&at("2026-10-19T06:30:00Z"), Tz::Europe__Madrid, 8)
+            .unwrap();
+        assert_eq!(due.first_day, day("2026-10-12"));
+
+        settings.last_sent_at = Some(at("2026-10-19T06:30:00Z"));
+        assert_eq!(
+            settings.due(&at("2026-10-22T12:00:00Z"), Tz::Europe__Madrid, 8),
+            None
+        );
+        assert!(settings
+            .due(&at("2026-10-26T07:00:00Z"), Tz::Europe__Madrid, 8)
+            .is_some());
+    }
+
+    #[test]
+    fn digests_summarize_the_period_against_the_previous_one_and_the_goal() {
+        let period = DigestFrequency::Weekly.period_of(day("2026-10-12"));
+        let weights = [
+            weight("2026-10-12T08:00:00+02:00", 80.0),
+            weight("2026-10-15T08:00:00+02:00", 79.25),
+            weight("2026-10-18T08:00:00+02:00", 78.5),
+        ];
+        let previous_weights = [weight("2026-10-08T08:00:00+02:00", 81.0)];
+
+        let digest = Digest::new(
+            period,
+            &weights,
+            &previous_weights,
+            Some(&Kilograms::new(75.0).unwrap()),
+            &Tz::Europe__Madrid,
+        )
+        .unwrap();
+
+        assert_eq!(digest.weigh_ins, 3);
+        assert_eq!(digest.average, 79.25);
+        assert_eq!((digest.minimum, digest.maximum), (78.5, 80.0));
+        assert_eq!(digest.change, Some(-1.75));
+        assert!((digest.trend.unwrap() + 1.75).abs() < 1e-9);
+        let goal = digest.goal.unwrap();
+        assert_eq!((goal.remaining, goal.closer), (3.5, 1.5));
+        assert!(Digest::new(period, &[], &previous_weights, None, &Tz::Europe__Madrid).is_none());
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::error::ApiError;
+
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn parse(value: &str) -> Result<Self, ApiError> {
+        match value {
+            "asc" => Ok(Self::Ascending),
+            "desc" => Ok(Self::Descending),
+            _ => Err(ApiError::InvalidSortOrder),
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ascending => write!(f, "asc"),
+            Self::Descending => write!(f, "desc"),
+        }
+    }
+}
+
+/// Position after the last measurement of a page: its instant and its id, which breaks the ties
+/// between measurements taken at the same instant.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cursor {
+    pub measured_at: DateTime<Utc>,
+    pub id: i64,
+}
+
+impl Cursor {
+    pub fn new(measured_at: &DateTime<FixedOffset>, id: i64) -> Self {
+        Cursor {
+            measured_at: measured_at.to_utc(),
+            id,
+        }
+    }
+
+    /// Parses a cursor as formatted by its `Display`, clients should treat it as opaque.
+    pub fn parse(value: &str) -> Result<Self, ApiError> {
+        let (micros, id) = value.split_once('.').ok_or(ApiError::InvalidCursor)?;
+        let micros: i64 = micros.parse().map_err(|_| ApiError::InvalidCursor)?;
+
+        Ok(Cursor {
+            measured_at: DateTime::from_timestamp_micros(micros).ok_or(ApiError::InvalidCursor)?,
+            id: id.parse().map_err(|_| ApiError::InvalidCursor)?,
+        })
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.measured_at.timestamp_micros(), self.id)
+    }
+}
+
+/// A slice of the measurements of a user, optionally bounded by dates, starting after the cursor.
+pub struct PageRequest {
+    pub start_date: Option<DateTime<FixedOffset>>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+    pub order: SortOrder,
+    pub after: Option<Cursor>,
+    pub limit: usize,
+}
+
+pub fn parse_limit(value: &str) -> Result<usize, ApiError> {
+    match value.parse() {
+        Ok(limit) if (1..=MAX_PAGE_LIMIT).contains(&limit) => Ok(limit),
+        _ => Err(ApiError::InvalidPageLimit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_survives_a_round_trip() {
+        let measured_at = DateTime::parse_from_rfc3339("2024-01-01T20:00:00.123456-05:00").unwrap();
+        let cursor = Cursor::new(&measured_at, 42);
+
+        assert_eq!(Cursor::parse(&cursor.to_string()).unwrap(), cursor);
+        assert!(Cursor::parse("42").is_err());
+        assert!(Cursor::parse("abc.42").is_err());
+    }
+
+    #[test]
+    fn limit_must_be_within_bounds() {
+        assert_eq!(parse_limit("50").unwrap(), 50);
+        assert!(parse_limit("0").is_err());
+        assert!(parse_limit("1001").is_err());
+        assert!(parse_limit("-1").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use lettre::Address;
+
+use crate::error::ApiError;
+
+use super::user::UserId;
+
+/// How a user wants to be reminded to weigh in, users without them are never reminded.
+pub struct ReminderSettings {
+    pub user_id: UserId,
+    /// Days without weighing in before the first reminder, the configured ones when there are none.
+    pub after_days: Option<u32>,
+    pub email: Option<Address>,
+    pub ntfy_topic: Option<String>,
+    pub quiet_hours: Option<QuietHours>,
+    pub last_reminded_at: Option<DateTime<Utc>>,
+}
+
+impl ReminderSettings {
+    /// Days since the latest weight when a reminder is due now, none when it is not. After the
+    /// first one, reminders are repeated once a day while the user does not weigh in.
+    pub fn due(
+        &self,
+        default_after_days: u32,
+        latest: &DateTime<FixedOffset>,
+        now: &DateTime<Utc>,
+        timezone: Tz,
+    ) -> Option<i64> {
+        let days = (*now - latest.to_utc()).num_days();
+        if days < self.after_days.unwrap_or(default_after_days).into() {
+            return None;
+        }
+
+        if self
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|quiet_hours| quiet_hours.contains(now.with_timezone(&timezone).time()))
+        {
+            return None;
+        }
+
+        let reminded_recently = self.last_reminded_at.is_some_and(|reminded_at| {
+            reminded_at > latest.to_utc() && *now - reminded_at < TimeDelta::days(1)
+        });
+        if reminded_recently {
+            return None;
+        }
+
+        Some(days)
+    }
+}
+
+/// Local times of the day reminders are not sent in, spanning midnight when the end is before the
+/// start.
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+/// Parses a time of the day as `HH:MM`.
+pub fn parse_time(value: &str) -> Result<NaiveTime, ApiError> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| ApiError::InvalidQuietHours)
+}
+
+pub fn format_time(time: &NaiveTime) -> String {
+    time.format("%H:%M").to_string()
+}
+
+pub fn parse_email(value: &str) -> Result<Address, ApiError> {
+    value.parse().map_err(|_| ApiError::InvalidEmail)
+}
+
+/// Topics are part of the URL of the ntfy server, so only the characters it accepts are allowed.
+pub fn parse_ntfy_topic(value: &str) -> Result<String, ApiError> {
+    let valid = (1..=64).contains(&value.len())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ApiError::InvalidNtfyTopic);
+    }
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(quiet_hours: Option<(&str, &str)>) -> ReminderSettings {
+        ReminderSettings {
+            user_id: UserId::new(1),
+            after_days: None,
+            email: None,
+            ntfy_topic: None,
+            quiet_hours: quiet_hours.map(|(start, end)| QuietHours {
+                start: parse_time(start).unwrap(),
+                end: parse_time(end).unwrap(),
+            }),
+            last_reminded_at: None,
+        }
+    }
+
+    fn date(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_can_span_midnight() {
+        let night = settings(Some(("22:00", "07:30"))).quiet_hours.unwrap();
+        let afternoon = settings(Some(("14:00", "16:00"))).quiet_hours.unwrap();
+
+        assert!(night.contains(parse_time("23:15").unwrap()));
+        assert!(night.contains(parse_time("06:00").unwrap()));
+        assert!(!night.contains(parse_time("07:30").unwrap()));
+        assert!(afternoon.contains(parse_time("15:00").unwrap()));
+        assert!(!afternoon.contains(parse_time("21:00").unwrap()));
+    }
+
+    #[test]
+    fn reminders_are_due_after_the_days_without_weighing_in() {
+        let latest = date("2026-10-15T08:00:00+02:00");
+        let due =
+            |now: &str| settings(None).due(3, &latest, &date(now).to_utc(), Tz::Europe__Madrid);
+
+        assert_eq!(due("2026-10-17T12:00:00+02:00"), None);
+        assert_eq!(due("2026-10-18T08:00:00+02:00"), Some(3));
+        assert_eq!(due("2026-10-20T12:00:00+02:00"), Some(5));
+    }
+
+    #[test]
+    fn reminders_wait_for_the_end_of_the_quiet_hours_in_the_timezone_of_the_user() {
+        let latest = date("2026-10-10T08:00:00+02:00");
+        let now = date("2026-10-19T21:30:00Z").to_utc();
+
+        // 23:30 in Madrid
+        assert_eq!(
+            settings(Some(("22:00", "07:00"))).due(2, &latest, &now, Tz::Europe__Madrid),
+            None
+        );
+        // 17:30 in New York
+        assert_eq!(
+            settings(Some(("22:00", "07:00"))).due(2, &latest, &now, Tz::America__New_York),
+            Some(9)
+        );
+    }
+
+    #[test]
+    fn reminders_are_repeated_once_a_day() {
+        let latest = date("2026-10-10T08:00:00+02:00");
+        let mut reminded = settings(None);
+        reminded.last_reminded_at = Some(date("2026-10-19T09:00:00+02:00").to_utc());
+        let due = |now: &str| reminded.due(2, &latest, &date(now).to_utc(), Tz::UTC);
+
+        assert_eq!(due("2026-10-19T20:00:00+02:00"), None);
+        assert_eq!(due("2026-10-20T09:00:00+02:00"), Some(10));
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Datelike, FixedOffset};
+use chrono_tz::Tz;
+
+use super::{
+    calendar,
+    impedance::Impedance,
+    user::Centimeters,
+    weight::{self, Weight},
+};
+
+/// Weights of a calendar month in the timezone of the user.
+#[derive(PartialEq, Debug)]
+pub struct MonthSummary {
+    pub year: i32,
+    pub month: u32,
+    pub weigh_ins: usize,
+    pub average: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+    /// From the first weight of the month to the last one.
+    pub change: f64,
+}
+
+/// Summary of each month with weights, oldest first, for weights sorted the same way.
+pub fn monthly_summaries(weights: &[Weight], timezone: &Tz) -> Vec<MonthSummary> {
+    let mut months: Vec<((i32, u32), Vec<f64>)> = Vec::new();
+    for weight in weights {
+        let day = calendar::day(&weight.measured_at, timezone);
+        let month = (day.year(), day.month());
+        let kilograms: f64 = (&weight.kilograms).into();
+
+        match months.last_mut() {
+            Some((last, kilograms_of_month)) if *last == month => {
+                kilograms_of_month.push(kilograms)
+            }
+            _ => months.push((month, vec![kilograms])),
+        }
+    }
+
+    months
+        .into_iter()
+        .map(|((year, month), kilograms)| MonthSummary {
+            year,
+            month,
+            weigh_ins: kilograms.len(),
+            average: kilograms.iter().sum::<f64>() / kilograms.len() as f64,
+            minimum: kilograms.iter().copied().fold(f64::INFINITY, f64::min),
+            maximum: kilograms.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            change: kilograms.last().unwrap_or(&0.0) - kilograms.first().unwrap_or(&0.0),
+        })
+        .collect()
+}
+
+/// Impedances of the range, for the body composition of the report.
+pub struct BodyComposition {
+    pub measurements: usize,
+    pub average_ohms: f64,
+    pub latest_ohms: f64,
+    pub latest_measured_at: DateTime<FixedOffset>,
+    /// Height squared over the latest impedance in cm²/Ω, the usual predictor of the fat-free
+    /// mass, none when the height of the user is unknown.
+    pub impedance_index: Option<f64>,
+}
+
+impl BodyComposition {
+    /// None when there are no impedances, which are sorted oldest first.
+    pub fn new(impedances: &[Impedance], height: Option<&Centimeters>) -> Option<Self> {
+        let latest = impedances.last()?;
+        let latest_ohms: f64 = (&latest.ohms).into();
+        let average_ohms = impedances
+            .iter()
+            .map(|impedance| f64::from(&impedance.ohms))
+            .sum::<f64>()
+            / impedances.len() as f64;
+
+        Some(BodyComposition {
+            measurements: impedances.len(),
+            average_ohms,
+            latest_ohms,
+            latest_measured_at: latest.measured_at,
+            impedance_index: height
+                .map(|height| f64::from(height).powi(2) / latest_ohms)
+                .filter(|index| index.is_finite()),
+        })
+    }
+}
+
+/// Weights of the range, none when there are none.
+pub struct WeightSummary {
+    pub weigh_ins: usize,
+    pub first: f64,
+    pub latest: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+    /// Kilograms per week, none when the weights are not spread over more than one day.
+    pub trend: Option<f64>,
+    /// Body mass index of the latest weight when the height of the user is known.
+    pub bmi: Option<f64>,
+}
+
+impl WeightSummary {
+    pub fn new(weights: &[Weight], height: Option<&Centimeters>, timezone: &Tz) -> Option<Self> {
+        let (first, latest) = (weights.first()?, weights.last()?);
+        let kilograms = || weights.iter().map(|weight| f64::from(&weight.kilograms));
+
+        Some(WeightSummary {
+            weigh_ins: weights.len(),
+            first: (&first.kilograms).into(),
+            latest: (&latest.kilograms).into(),
+            minimum: kilograms().fold(f64::INFINITY, f64::min),
+            maximum: kilograms().fold(f64::NEG_INFINITY, f64::max),
+            trend: Some(weight::slope(weights, timezone) * 7.0).filter(|trend| trend.is_finite()),
+            bmi: height.map(|height| latest.kilograms.body_mass_index(height)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        user::UserId,
+        weight::{Kilograms, WeightId},
+    };
+
+    use super::*;
+
+    fn weight(measured_at: &str, kilograms: f64) -> Weight {
+        Weight {
+            weight_id: WeightId::new(1),
+            user_id: UserId::new(1),
+            measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+            kilograms: Kilograms::new(kilograms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn months_are_summarized_in_the_timezone_of_the_user() {
+        let weights = [
+            weight("2026-08-20T08:00:00+02:00", 82.0),
+            // Still August in New York
+            weight("2026-09-01T01:00:00+00:00", 81.0),
+            weight("2026-09-05T08:00:00+02:00", 80.5),
+            weight("2026-09-25T08:00:00+02:00", 79.5),
+        ];
+
+        let summaries = monthly_summaries(&weights, &Tz::America__New_York);
+
+        assert_eq!(
+            summaries,
+            vec![
+                MonthSummary {
+                    year: 2026,
+                    month: 8,
+                    weigh_ins: 2,
+                    average: 81.5,
+                    minimum: 81.0,
+                    maximum: 82.0,
+                    change: -1.0,
+                },
+                MonthSummary {
+                    year: 2026,
+                    month: 9,
+                    weigh_ins: 2,
+                    average: 80.0,
+                    minimum: 79.5,
+                    maximum: 80.5,
+                    change: -1.0,
+                },
+            ]
+        );
+        assert_eq!(
+            monthly_summaries(&weights, &Tz::Europe__Madrid)[1].weigh_ins,
+            3
+        );
+    }
+}
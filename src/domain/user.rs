@@ -1,6 +1,42 @@
+use chrono_tz::Tz;
+
+use crate::error::ApiError;
+
+use super::weight::Kilograms;
+
 pub struct User {
     pub id: UserId,
     pub name: UserName,
+    pub height: Option<Centimeters>,
+    /// Home timezone, used to split the measurements into days.
+    pub timezone: Option<Tz>,
+    /// Weight the user wants to reach.
+    pub goal: Option<Kilograms>,
+}
+
+impl User {
+    /// Home timezone of the user, or the given one when the user has none.
+    pub fn timezone_or(&self, default: Tz) -> Tz {
+        self.timezone.unwrap_or(default)
+    }
+
+    /// Whether going from the previous latest weight to the new one reaches the goal, from above
+    /// when losing weight or from below when gaining it.
+    pub fn reaches_goal(&self, previous: &Kilograms, current: &Kilograms) -> bool {
+        let Some(goal) = &self.goal else {
+            return false;
+        };
+
+        let before = f64::from(previous) - f64::from(goal);
+        let after = f64::from(current) - f64::from(goal);
+
+        before != 0.0 && (after == 0.0 || before.signum() != after.signum())
+    }
+}
+
+/// Parses an IANA timezone name such as `Europe/Madrid`.
+pub fn parse_timezone(value: &str) -> Result<Tz, ApiError> {
+    value.parse::<Tz>().map_err(|_| ApiError::InvalidTimezone)
 }
 
 pub struct UserId(i64);
@@ -60,3 +96,87 @@ impl From<&UserName> for String {
         value.0.clone()
     }
 }
+
+#[derive(Clone)]
+pub struct Centimeters(f64);
+
+impl Centimeters {
+    pub fn new(value: f64) -> Result<Centimeters, ApiError> {
+        if value <= 0.0 {
+            return Err(ApiError::NonPositiveHeight);
+        }
+        Ok(Centimeters(value))
+    }
+}
+
+impl From<Centimeters> for f64 {
+    fn from(value: Centimeters) -> Self {
+        value.0
+    }
+}
+
+impl From<&Centimeters> for f64 {
+    fn from(value: &Centimeters) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<f64> for Centimeters {
+    type Error = ApiError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_height_is_invalid() -> Result<(), String> {
+        match Centimeters::try_from(0.0) {
+            Ok(_) => Err("Height must be greater than zero".to_string()),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn user_with_goal(goal: f64) -> User {
+        User {
+            id: UserId::new(1),
+            name: UserName::new("user".to_string()),
+            height: None,
+            timezone: None,
+            goal: Some(Kilograms::new(goal).unwrap()),
+        }
+    }
+
+    #[test]
+    fn goal_is_reached_when_crossed_from_either_side() {
+        let kilograms = |value: f64| Kilograms::new(value).unwrap();
+        let user = user_with_goal(80.0);
+
+        assert!(user.reaches_goal(&kilograms(80.5), &kilograms(79.8)));
+        assert!(user.reaches_goal(&kilograms(79.0), &kilograms(80.0)));
+        assert!(!user.reaches_goal(&kilograms(81.0), &kilograms(80.5)));
+        assert!(!user.reaches_goal(&kilograms(80.0), &kilograms(79.0)));
+        assert!(!user.reaches_goal(&kilograms(79.0), &kilograms(78.0)));
+    }
+
+    #[test]
+    fn unknown_timezone_is_invalid() {
+        assert!(parse_timezone("Europe/Madrid").is_ok());
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn positive_height_is_valid() -> Result<(), String> {
+        match Centimeters::try_from(175.0) {
+            Ok(h) => {
+                assert_eq!(h.0, 175.0, "Height does not match");
+                Ok(())
+            }
+            Err(_) => Err("Height must be possitive".to_string()),
+        }
+    }
+}
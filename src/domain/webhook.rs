@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+
+use crate::error::ApiError;
+
+use super::user::UserId;
+
+pub struct Webhook {
+    pub webhook_id: WebhookId,
+    /// Only the events of this user are sent, the events of every user when there is none.
+    pub user_id: Option<UserId>,
+    pub url: WebhookUrl,
+    /// Key of the HMAC signature of every delivery.
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether the event of the user has to be sent to this webhook.
+    pub fn wants(&self, event: WebhookEvent, user_id: i64) -> bool {
+        self.events.contains(&event)
+            && self
+                .user_id
+                .as_ref()
+                .is_none_or(|webhook_user_id| *webhook_user_id.as_ref() == user_id)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WebhookId(i64);
+
+impl WebhookId {
+    pub fn new(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<WebhookId> for i64 {
+    fn from(value: WebhookId) -> Self {
+        value.0
+    }
+}
+
+impl From<&WebhookId> for i64 {
+    fn from(value: &WebhookId) -> Self {
+        value.0
+    }
+}
+
+/// Absolute `http` or `https` URL the deliveries are posted to.
+#[derive(Clone)]
+pub struct WebhookUrl(Url);
+
+impl WebhookUrl {
+    pub fn new(value: &str) -> Result<WebhookUrl, ApiError> {
+        let url = Url::parse(value).map_err(|_| ApiError::InvalidWebhookUrl)?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ApiError::InvalidWebhookUrl);
+        }
+        Ok(WebhookUrl(url))
+    }
+
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+}
+
+impl From<&WebhookUrl> for String {
+    fn from(value: &WebhookUrl) -> Self {
+        value.0.to_string()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WebhookEvent {
+    MeasurementCreated,
+    MeasurementDeleted,
+    GoalReached,
+    /// The user has not weighed in for the days of its reminders.
+    ReminderDue,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 4] = [
+        WebhookEvent::MeasurementCreated,
+        WebhookEvent::MeasurementDeleted,
+        WebhookEvent::GoalReached,
+        WebhookEvent::ReminderDue,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::MeasurementCreated => "measurement.created",
+            WebhookEvent::MeasurementDeleted => "measurement.deleted",
+            WebhookEvent::GoalReached => "goal.reached",
+            WebhookEvent::ReminderDue => "reminder.due",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<WebhookEvent, ApiError> {
+        Self::ALL
+            .into_iter()
+            .find(|event| event.name() == name)
+            .ok_or(ApiError::InvalidWebhookEvent)
+    }
+}
+
+/// Parses the comma separated event names the webhooks are stored with.
+pub fn parse_events(value: &str) -> Result<Vec<WebhookEvent>, ApiError> {
+    value
+        .split(',')
+        .filter(|name| !name.is_empty())
+        .map(WebhookEvent::parse)
+        .collect()
+}
+
+/// Inverse of `parse_events`.
+pub fn format_events(events: &[WebhookEvent]) -> String {
+    events
+        .iter()
+        .map(WebhookEvent::name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// One attempt to deliver an event to a webhook.
+pub struct Delivery {
+    pub webhook_id: WebhookId,
+    /// Name of the event, `ping` for test deliveries.
+    pub event: String,
+    pub payload: String,
+    /// Starting at one, retries increase it.
+    pub attempt: u32,
+    pub attempted_at: DateTime<Utc>,
+    /// Status of the response, none when the request failed before getting one.
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub succeeded: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_stored_by_name() -> Result<(), ApiError> {
+        let events = vec![WebhookEvent::MeasurementCreated, WebhookEvent::GoalReached];
+
+        assert_eq!(
+            format_events(&events),
+            "measurement.created,goal.reached".to_string()
+        );
+        assert_eq!(parse_events(&format_events(&events))?, events);
+        assert!(parse_events("measurement.updated").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn only_http_urls_are_valid() {
+        assert!(WebhookUrl::new("https://example.com/hooks/weights").is_ok());
+        assert!(WebhookUrl::new("http://127.0.0.1:8080").is_ok());
+        assert!(WebhookUrl::new("ftp://example.com").is_err());
+        assert!(WebhookUrl::new("/hooks/weights").is_err());
+    }
+}
@@ -1,8 +1,12 @@
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, Datelike, FixedOffset};
+use chrono_tz::Tz;
 
 use crate::error::ApiError;
 
-use super::user::UserId;
+use super::{
+    calendar,
+    user::{Centimeters, UserId},
+};
 
 pub struct Weight {
     pub weight_id: WeightId,
@@ -43,6 +47,14 @@ impl Kilograms {
     }
 }
 
+impl Kilograms {
+    /// Body mass index for a person of the given height.
+    pub fn body_mass_index(&self, height: &Centimeters) -> f64 {
+        let meters: f64 = f64::from(height) / 100.0;
+        self.0 / (meters * meters)
+    }
+}
+
 impl From<Kilograms> for f64 {
     fn from(value: Kilograms) -> Self {
         value.0
@@ -63,6 +75,29 @@ impl TryFrom<f64> for Kilograms {
     }
 }
 
+/// Slope of the least squares line over the weights, in kilograms per day of the timezone.
+pub fn slope(weights: &[Weight], timezone: &Tz) -> f64 {
+    let n = weights.len() as f64;
+
+    let weight_kilograms: Vec<f64> = weights.iter().map(|w| (&w.kilograms).into()).collect();
+
+    let timestamps: Vec<f64> = weights
+        .iter()
+        .map(|w| calendar::day(&w.measured_at, timezone).num_days_from_ce() as f64)
+        .collect();
+
+    let sum_x: f64 = timestamps.iter().sum();
+    let sum_x_square: f64 = timestamps.iter().map(|v| v * v).sum();
+    let sum_y: f64 = weight_kilograms.iter().sum();
+    let mut sum_xy: f64 = 0.0;
+
+    for i in 0..weights.len() {
+        sum_xy += weight_kilograms[i] * timestamps[i];
+    }
+
+    (n * sum_xy - sum_x * sum_y) / (n * sum_x_square - (sum_x * sum_x))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +124,34 @@ mod tests {
             Err(_) => Err("Weight must be possitive".to_string()),
         }
     }
+
+    #[test]
+    fn body_mass_index_is_calculated_from_height() -> Result<(), ApiError> {
+        let kilograms = Kilograms::new(81.0)?;
+        let height = Centimeters::new(180.0)?;
+
+        assert_eq!(kilograms.body_mass_index(&height), 25.0);
+        Ok(())
+    }
+
+    #[test]
+    fn slope_counts_the_days_of_the_timezone() -> Result<(), ApiError> {
+        let weight = |measured_at: &str, kilograms: f64| -> Result<Weight, ApiError> {
+            Ok(Weight {
+                weight_id: WeightId::new(0),
+                user_id: UserId::new(1),
+                measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+                kilograms: Kilograms::new(kilograms)?,
+            })
+        };
+        // Stored in UTC the late one is on the 18th, but it is the 19th in Madrid
+        let weights = [
+            weight("2026-10-17T08:00:00+02:00", 80.0)?,
+            weight("2026-10-18T23:30:00+00:00", 79.0)?,
+        ];
+
+        assert_eq!(slope(&weights, &Tz::Europe__Madrid), -0.5);
+        assert_eq!(slope(&weights, &Tz::UTC), -1.0);
+        Ok(())
+    }
 }
@@ -3,7 +3,24 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
+use std::fmt;
+
+/// Body of every error response of the unversioned API.
+#[derive(Serialize)]
+pub struct ErrorResponse {
+    message: String,
+}
+
+/// What an error response is about, attached to it so that the versioned API can describe it as a
+/// problem.
+#[derive(Clone, Debug)]
+pub struct ErrorDetails {
+    pub code: &'static str,
+    /// Parameter or field of the body the error is about.
+    pub field: Option<&'static str>,
+    pub detail: String,
+}
 
 #[derive(Debug)]
 pub enum ApiError {
@@ -12,19 +29,132 @@ pub enum ApiError {
     MandatoryStartDate,
     MandatoryEndDate,
     InvalidUserId,
-    InvalidDateTime,
+    /// The field that holds the date.
+    InvalidDateTime(&'static str),
+    InvalidTimezone,
+    InvalidPageLimit,
+    InvalidCursor,
+    InvalidSortOrder,
+    InvalidMeasurement,
     StartDateGreaterThanEndDate,
     NegativeWeight,
+    NonPositiveHeight,
     WeightNotFound,
     ImpedanceNotFound,
-    UnsupportedMediaType,
+    WebhookNotFound,
+    InvalidWebhookUrl,
+    InvalidWebhookEvent,
+    RemindersNotFound,
+    InvalidEmail,
+    InvalidQuietHours,
+    InvalidReminderDays,
+    InvalidNtfyTopic,
+    DigestNotFound,
+    InvalidDigestFrequency,
+    InvalidChartSize(&'static str),
+    InvalidReportNotes,
+    NotAcceptable,
+    Unauthorized,
+    /// No admin token is configured, so nobody can be authorized.
+    AdminDisabled,
+    SnapshotNotSupported,
 
-    Unexpected(Box<dyn std::error::Error>),
+    Unexpected(Box<dyn std::error::Error + Send + Sync>),
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        let (status, err_msg) = match self {
+impl ApiError {
+    /// Stable identifier of the error for clients of the versioned API.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UserNotFound => "user_not_found",
+            Self::MandatoryUserId => "mandatory_user_id",
+            Self::MandatoryStartDate => "mandatory_start_date",
+            Self::MandatoryEndDate => "mandatory_end_date",
+            Self::InvalidUserId => "invalid_user_id",
+            Self::InvalidDateTime(_) => "invalid_date_time",
+            Self::InvalidTimezone => "invalid_timezone",
+            Self::InvalidPageLimit => "invalid_page_limit",
+            Self::InvalidCursor => "invalid_cursor",
+            Self::InvalidSortOrder => "invalid_sort_order",
+            Self::InvalidMeasurement => "invalid_measurement",
+            Self::StartDateGreaterThanEndDate => "start_date_after_end_date",
+            Self::NegativeWeight => "negative_weight",
+            Self::NonPositiveHeight => "non_positive_height",
+            Self::WeightNotFound => "weight_not_found",
+            Self::ImpedanceNotFound => "impedance_not_found",
+            Self::WebhookNotFound => "webhook_not_found",
+            Self::InvalidWebhookUrl => "invalid_webhook_url",
+            Self::InvalidWebhookEvent => "invalid_webhook_event",
+            Self::RemindersNotFound => "reminders_not_found",
+            Self::InvalidEmail => "invalid_email",
+            Self::InvalidQuietHours => "invalid_quiet_hours",
+            Self::InvalidReminderDays => "invalid_reminder_days",
+            Self::InvalidNtfyTopic => "invalid_ntfy_topic",
+            Self::DigestNotFound => "digest_not_found",
+            Self::InvalidDigestFrequency => "invalid_digest_frequency",
+            Self::InvalidChartSize(_) => "invalid_chart_size",
+            Self::InvalidReportNotes => "invalid_report_notes",
+            Self::NotAcceptable => "not_acceptable",
+            Self::Unauthorized => "unauthorized",
+            Self::AdminDisabled => "admin_disabled",
+            Self::SnapshotNotSupported => "snapshot_not_supported",
+            Self::Unexpected(_) => "internal_error",
+        }
+    }
+
+    /// Parameter or field of the body that failed validation, none for the other errors.
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            Self::MandatoryUserId | Self::InvalidUserId => Some("user_id"),
+            Self::MandatoryStartDate | Self::StartDateGreaterThanEndDate => Some("start_date"),
+            Self::MandatoryEndDate => Some("end_date"),
+            Self::InvalidDateTime(field) => Some(field),
+            Self::InvalidTimezone => Some("timezone"),
+            Self::InvalidPageLimit => Some("limit"),
+            Self::InvalidCursor => Some("cursor"),
+            Self::InvalidSortOrder => Some("order"),
+            Self::InvalidMeasurement => Some("measurement"),
+            Self::NegativeWeight => Some("kilograms"),
+            Self::NonPositiveHeight => Some("height_centimeters"),
+            Self::InvalidWebhookUrl => Some("url"),
+            Self::InvalidWebhookEvent => Some("events"),
+            Self::InvalidEmail => Some("email"),
+            Self::InvalidQuietHours => Some("quiet_hours_start"),
+            Self::InvalidReminderDays => Some("after_days"),
+            Self::InvalidNtfyTopic => Some("ntfy_topic"),
+            Self::InvalidDigestFrequency => Some("frequency"),
+            Self::InvalidChartSize(field) => Some(field),
+            Self::InvalidReportNotes => Some("notes"),
+            Self::UserNotFound
+            | Self::WeightNotFound
+            | Self::ImpedanceNotFound
+            | Self::WebhookNotFound
+            | Self::RemindersNotFound
+            | Self::DigestNotFound
+            | Self::NotAcceptable
+            | Self::Unauthorized
+            | Self::AdminDisabled
+            | Self::SnapshotNotSupported
+            | Self::Unexpected(_) => None,
+        }
+    }
+
+    fn details(&self) -> ErrorDetails {
+        // The message of the unversioned API is kept as it was, only naming the field here
+        let detail = match self {
+            Self::InvalidDateTime(field) => format!("{} must be a valid date", field),
+            _ => self.status_and_message().1,
+        };
+
+        ErrorDetails {
+            code: self.code(),
+            field: self.field(),
+            detail,
+        }
+    }
+
+    fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
             Self::UserNotFound => (StatusCode::NOT_FOUND, "User not found".to_string()),
             Self::MandatoryUserId => (
                 StatusCode::BAD_REQUEST,
@@ -39,10 +169,30 @@ impl IntoResponse for ApiError {
                 "Query parameter end_date is mandatory".to_string(),
             ),
             Self::InvalidUserId => (StatusCode::BAD_REQUEST, "user_id must be valid".to_string()),
-            Self::InvalidDateTime => (
+            Self::InvalidDateTime(_) => (
                 StatusCode::BAD_REQUEST,
                 "date_time must be a valid date".to_string(),
             ),
+            Self::InvalidTimezone => (
+                StatusCode::BAD_REQUEST,
+                "timezone must be a valid IANA timezone".to_string(),
+            ),
+            Self::InvalidPageLimit => (
+                StatusCode::BAD_REQUEST,
+                "limit must be between 1 and 1000".to_string(),
+            ),
+            Self::InvalidCursor => (
+                StatusCode::BAD_REQUEST,
+                "cursor must be one returned in a Link header".to_string(),
+            ),
+            Self::InvalidSortOrder => (
+                StatusCode::BAD_REQUEST,
+                "order must be asc or desc".to_string(),
+            ),
+            Self::InvalidMeasurement => (
+                StatusCode::BAD_REQUEST,
+                "measurement must be weights or impedances".to_string(),
+            ),
             Self::StartDateGreaterThanEndDate => (
                 StatusCode::BAD_REQUEST,
                 "start_date cannot be greater than end_date".to_string(),
@@ -51,20 +201,103 @@ impl IntoResponse for ApiError {
                 StatusCode::BAD_REQUEST,
                 "Weight cannot be negative".to_string(),
             ),
+            Self::NonPositiveHeight => (
+                StatusCode::BAD_REQUEST,
+                "Height must be greater than zero".to_string(),
+            ),
             Self::WeightNotFound => (StatusCode::NOT_FOUND, "Weight not found".to_string()),
             Self::ImpedanceNotFound => (StatusCode::NOT_FOUND, "Impedance not found".to_string()),
-            Self::UnsupportedMediaType => (
-                StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                "media type not supported".to_string(),
-            ),
-            Self::Unexpected(error) => {
-                println!("Unexpected error ocurred. {}", error);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
-            }
-        };
-        (status, Json(json!({"message": err_msg }))).into_response()
+            Self::WebhookNotFound => (StatusCode::NOT_FOUND, "Webhook not found".to_string()),
+            Self::InvalidWebhookUrl => (
+                StatusCode::BAD_REQUEST,
+                "url must be an absolute http or https URL".to_string(),
+            ),
+            Self::InvalidWebhookEvent => (
+                StatusCode::BAD_REQUEST,
+                "events must be measurement.created, measurement.deleted, goal.reached or reminder.due"
+                    .to_string(),
+            ),
+            Self::RemindersNotFound => (
+                StatusCode::NOT_FOUND,
+                "The user has no reminders".to_string(),
+            ),
+            Self::InvalidEmail => (
+                StatusCode::BAD_REQUEST,
+                "email must be a valid address".to_string(),
+            ),
+            Self::InvalidQuietHours => (
+                StatusCode::BAD_REQUEST,
+                "quiet_hours_start and quiet_hours_end must be both set as HH:MM".to_string(),
+            ),
+            Self::InvalidReminderDays => (
+                StatusCode::BAD_REQUEST,
+                "after_days must be greater than zero".to_string(),
+            ),
+            Self::InvalidNtfyTopic => (
+                StatusCode::BAD_REQUEST,
+                "ntfy_topic must be up to 64 letters, digits, - or _".to_string(),
+            ),
+            Self::DigestNotFound => (
+                StatusCode::NOT_FOUND,
+                "The user has no digest".to_string(),
+            ),
+            Self::InvalidDigestFrequency => (
+                StatusCode::BAD_REQUEST,
+                "frequency must be weekly or monthly".to_string(),
+            ),
+            Self::InvalidChartSize(field) => (
+                StatusCode::BAD_REQUEST,
+                format!("{} must be between 50 and 4000 pixels", field),
+            ),
+            Self::InvalidReportNotes => (
+                StatusCode::BAD_REQUEST,
+                "notes must be at most 2000 characters".to_string(),
+            ),
+            Self::NotAcceptable => (
+                StatusCode::NOT_ACCEPTABLE,
+                "None of the accepted media types can be produced".to_string(),
+            ),
+            Self::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "A valid admin token is required".to_string(),
+            ),
+            Self::AdminDisabled => (
+                StatusCode::FORBIDDEN,
+                "The admin endpoints are disabled until an admin token is configured".to_string(),
+            ),
+            Self::SnapshotNotSupported => (
+                StatusCode::NOT_IMPLEMENTED,
+                "Snapshots are only supported with SQLite".to_string(),
+            ),
+            Self::Unexpected(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unexpected(error) => write!(f, "Unexpected error ocurred. {}", error),
+            _ => write!(f, "{}", self.status_and_message().1),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Self::Unexpected(error) = &self {
+            tracing::error!(%error, "Unexpected error ocurred");
+        }
+
+        let (status, message) = self.status_and_message();
+        let mut response = (status, Json(ErrorResponse { message })).into_response();
+        response.extensions_mut().insert(self.details());
+
+        response
     }
 }
@@ -0,0 +1,212 @@
+use async_stream::stream;
+use chrono::{DateTime, FixedOffset};
+use futures::{
+    future,
+    stream::{BoxStream, StreamExt},
+};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+
+use crate::export::Exportable;
+
+/// Events kept for subscribers that are slow to read them, older ones are dropped.
+const CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Inserted,
+    Deleted,
+}
+
+/// A change to the measurements of a user.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct MeasurementEvent {
+    pub user_id: i64,
+    /// `weight` or `impedance`.
+    pub measurement: &'static str,
+    pub id: i64,
+    pub change: Change,
+    pub measured_at: DateTime<FixedOffset>,
+    pub value: f64,
+    pub unit: &'static str,
+}
+
+impl MeasurementEvent {
+    pub fn new<T: Exportable>(user_id: i64, measurement: &T, change: Change) -> Self {
+        MeasurementEvent {
+            user_id,
+            measurement: T::KIND,
+            id: measurement.id(),
+            change,
+            measured_at: *measurement.measured_at(),
+            value: measurement.value(),
+            unit: T::UNIT,
+        }
+    }
+}
+
+/// A new weight crossed the goal of the user.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct GoalEvent {
+    pub user_id: i64,
+    pub goal_kilograms: f64,
+    pub weight_id: i64,
+    pub measured_at: DateTime<FixedOffset>,
+    pub kilograms: f64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Event {
+    Measurement(MeasurementEvent),
+    GoalReached(GoalEvent),
+}
+
+impl Event {
+    pub fn user_id(&self) -> i64 {
+        match self {
+            Event::Measurement(event) => event.user_id,
+            Event::GoalReached(event) => event.user_id,
+        }
+    }
+}
+
+impl From<MeasurementEvent> for Event {
+    fn from(event: MeasurementEvent) -> Self {
+        Event::Measurement(event)
+    }
+}
+
+impl From<GoalEvent> for Event {
+    fn from(event: GoalEvent) -> Self {
+        Event::GoalReached(event)
+    }
+}
+
+/// What a subscriber receives: the events it subscribed to, or a notice that some were missed.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Notification {
+    Event(Event),
+    /// The subscriber fell behind and this many events were dropped, it has to read the
+    /// measurements again.
+    Lagged(u64),
+}
+
+/// Channel the changes to the measurements are published to as they happen.
+#[derive(Clone)]
+pub struct Events {
+    sender: broadcast::Sender<Event>,
+    closed: CancellationToken,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Events {
+            sender,
+            closed: CancellationToken::new(),
+        }
+    }
+
+    pub fn publish(&self, event: impl Into<Event>) {
+        // Nobody listening is not an error, the event is just not needed
+        let _ = self.sender.send(event.into());
+    }
+
+    /// Ends every subscription, so that the server can shut down without waiting for them.
+    pub fn close(&self) {
+        self.closed.cancel();
+    }
+
+    /// Notifications about the measurements of the user from now on, until the events are closed.
+    pub fn subscribe(&self, user_id: i64) -> BoxStream<'static, Notification> {
+        Box::pin(self.subscribe_all().filter(move |notification| {
+            future::ready(match notification {
+                Notification::Event(event) => event.user_id() == user_id,
+                Notification::Lagged(_) => true,
+            })
+        }))
+    }
+
+    /// Notifications about the measurements of every user from now on, until the events are
+    /// closed.
+    pub fn subscribe_all(&self) -> BoxStream<'static, Notification> {
+        let mut receiver = self.sender.subscribe();
+        let closed = self.closed.clone();
+
+        Box::pin(stream! {
+            loop {
+                let received = tokio::select! {
+                    received = receiver.recv() => received,
+                    _ = closed.cancelled() => break,
+                };
+
+                match received {
+                    Ok(event) => yield Notification::Event(event),
+                    Err(RecvError::Lagged(missed)) => yield Notification::Lagged(missed),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(user_id: i64, id: i64) -> Event {
+        Event::Measurement(MeasurementEvent {
+            user_id,
+            measurement: "weight",
+            id,
+            change: Change::Inserted,
+            measured_at: DateTime::parse_from_rfc3339("2026-10-19T08:00:00+02:00").unwrap(),
+            value: 80.0,
+            unit: "kg",
+        })
+    }
+
+    #[tokio::test]
+    async fn subscribers_only_get_the_events_of_their_user() {
+        let events = Events::new();
+        let mut subscription = events.subscribe(1);
+
+        events.publish(event(2, 1));
+        events.publish(event(1, 2));
+
+        assert_eq!(
+            subscription.next().await,
+            Some(Notification::Event(event(1, 2)))
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_are_told_what_they_missed() {
+        let events = Events::new();
+        let mut subscription = events.subscribe(1);
+
+        for id in 0..CAPACITY as i64 + 2 {
+            events.publish(event(1, id));
+        }
+
+        assert_eq!(subscription.next().await, Some(Notification::Lagged(2)));
+    }
+
+    #[tokio::test]
+    async fn closing_ends_the_subscriptions() {
+        let events = Events::new();
+        let subscription = events.subscribe(1);
+
+        events.close();
+
+        assert_eq!(subscription.collect::<Vec<_>>().await, vec![]);
+    }
+}
@@ -0,0 +1,637 @@
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use chrono::{DateTime, FixedOffset};
+use futures::{stream::BoxStream, Stream, StreamExt, TryStreamExt};
+use parquet::{
+    data_type::{DoubleType, Int32Type, Int64Type},
+    errors::ParquetError,
+    file::writer::SerializedFileWriter,
+    schema::parser::parse_message_type,
+};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde_json::{json, Value};
+use utoipa::{
+    openapi::{
+        schema::{KnownFormat, ObjectBuilder, SchemaFormat, Type},
+        RefOr, Schema,
+    },
+    PartialSchema, ToSchema,
+};
+
+use crate::{
+    domain::{impedance::Impedance, weight::Weight},
+    error::ApiError,
+};
+
+pub const WEIGHTS_CSV_HEADER: &str = "weight_id,measured_at,kilograms";
+pub const IMPEDANCES_CSV_HEADER: &str = "impedance_id,measured_at,ohms";
+pub const USER_MEASUREMENTS_CSV_HEADER: &str =
+    "user_id,user_name,measurement,id,measured_at,value,unit";
+
+/// Measurements streamed are written in chunks of this many rows.
+const CHUNK_ROWS: usize = 256;
+
+/// A kind of measurement, with the columns it is exported with.
+pub trait Exportable: Send + Sized + 'static {
+    const CSV_HEADER: &'static str;
+    const ID_COLUMN: &'static str;
+    const VALUE_COLUMN: &'static str;
+    const SHEET_NAME: &'static str;
+    /// Header of the value column in the workbooks, with its unit.
+    const VALUE_HEADER: &'static str;
+    /// Name of the kind in the exports that mix them.
+    const KIND: &'static str;
+    const UNIT: &'static str;
+
+    fn id(&self) -> i64;
+    fn measured_at(&self) -> &DateTime<FixedOffset>;
+    fn value(&self) -> f64;
+}
+
+impl Exportable for Weight {
+    const CSV_HEADER: &'static str = WEIGHTS_CSV_HEADER;
+    const ID_COLUMN: &'static str = "weight_id";
+    const VALUE_COLUMN: &'static str = "kilograms";
+    const SHEET_NAME: &'static str = "Weights";
+    const VALUE_HEADER: &'static str = "weight (kg)";
+    const KIND: &'static str = "weight";
+    const UNIT: &'static str = "kg";
+
+    fn id(&self) -> i64 {
+        (&self.weight_id).into()
+    }
+
+    fn measured_at(&self) -> &DateTime<FixedOffset> {
+        &self.measured_at
+    }
+
+    fn value(&self) -> f64 {
+        (&self.kilograms).into()
+    }
+}
+
+impl Exportable for Impedance {
+    const CSV_HEADER: &'static str = IMPEDANCES_CSV_HEADER;
+    const ID_COLUMN: &'static str = "impedance_id";
+    const VALUE_COLUMN: &'static str = "ohms";
+    const SHEET_NAME: &'static str = "Impedances";
+    const VALUE_HEADER: &'static str = "impedance (Ω)";
+    const KIND: &'static str = "impedance";
+    const UNIT: &'static str = "ohm";
+
+    fn id(&self) -> i64 {
+        (&self.impedance_id).into()
+    }
+
+    fn measured_at(&self) -> &DateTime<FixedOffset> {
+        &self.measured_at
+    }
+
+    fn value(&self) -> f64 {
+        (&self.ohms).into()
+    }
+}
+
+/// A row of the text exports, either a measurement of a user or one of any user.
+pub trait Row: Send + 'static {
+    const CSV_HEADER: &'static str;
+
+    fn csv_row(&self) -> String;
+    fn json_object(&self) -> Value;
+}
+
+impl<T: Exportable> Row for T {
+    const CSV_HEADER: &'static str = T::CSV_HEADER;
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.id(),
+            self.measured_at().to_rfc3339(),
+            self.value()
+        )
+    }
+
+    fn json_object(&self) -> Value {
+        json!({
+            T::ID_COLUMN: self.id(),
+            "measured_at": self.measured_at().to_rfc3339(),
+            T::VALUE_COLUMN: self.value(),
+        })
+    }
+}
+
+/// A measurement of any kind together with the user it belongs to, for the exports that cover
+/// every user.
+pub struct UserMeasurement {
+    pub user_id: i64,
+    pub user_name: Arc<str>,
+    /// `weight` or `impedance`.
+    pub measurement: &'static str,
+    pub id: i64,
+    pub measured_at: DateTime<FixedOffset>,
+    pub value: f64,
+    pub unit: &'static str,
+}
+
+impl UserMeasurement {
+    pub fn new<T: Exportable>(user_id: i64, user_name: Arc<str>, measurement: &T) -> Self {
+        UserMeasurement {
+            user_id,
+            user_name,
+            measurement: T::KIND,
+            id: measurement.id(),
+            measured_at: *measurement.measured_at(),
+            value: measurement.value(),
+            unit: T::UNIT,
+        }
+    }
+}
+
+impl Row for UserMeasurement {
+    const CSV_HEADER: &'static str = USER_MEASUREMENTS_CSV_HEADER;
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.user_id,
+            csv_field(&self.user_name),
+            self.measurement,
+            self.id,
+            self.measured_at.to_rfc3339(),
+            self.value,
+            self.unit
+        )
+    }
+
+    fn json_object(&self) -> Value {
+        json!({
+            "user_id": self.user_id,
+            "user_name": &*self.user_name,
+            "measurement": self.measurement,
+            "id": self.id,
+            "measured_at": self.measured_at.to_rfc3339(),
+            "value": self.value,
+            "unit": self.unit,
+        })
+    }
+}
+
+fn property(schema_type: Type, format: KnownFormat, description: &str) -> ObjectBuilder {
+    ObjectBuilder::new()
+        .schema_type(schema_type)
+        .format(Some(SchemaFormat::KnownFormat(format)))
+        .description(Some(description))
+}
+
+/// Schema of the JSON objects of the measurements, from the columns they are written with.
+fn json_schema<T: Exportable>() -> RefOr<Schema> {
+    ObjectBuilder::new()
+        .property(
+            T::ID_COLUMN,
+            property(Type::Integer, KnownFormat::Int64, "Id"),
+        )
+        .required(T::ID_COLUMN)
+        .property(
+            "measured_at",
+            property(
+                Type::String,
+                KnownFormat::DateTime,
+                "When it was measured, with the offset it was measured with",
+            ),
+        )
+        .required("measured_at")
+        .property(
+            T::VALUE_COLUMN,
+            property(Type::Number, KnownFormat::Double, T::VALUE_HEADER),
+        )
+        .required(T::VALUE_COLUMN)
+        .into()
+}
+
+impl PartialSchema for Weight {
+    fn schema() -> RefOr<Schema> {
+        json_schema::<Self>()
+    }
+}
+
+impl ToSchema for Weight {}
+
+impl PartialSchema for Impedance {
+    fn schema() -> RefOr<Schema> {
+        json_schema::<Self>()
+    }
+}
+
+impl ToSchema for Impedance {}
+
+impl PartialSchema for UserMeasurement {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .property(
+                "user_id",
+                property(Type::Integer, KnownFormat::Int64, "Id of the user"),
+            )
+            .required("user_id")
+            .property("user_name", ObjectBuilder::new().schema_type(Type::String))
+            .required("user_name")
+            .property(
+                "measurement",
+                ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .enum_values(Some([Weight::KIND, Impedance::KIND])),
+            )
+            .required("measurement")
+            .property(
+                "id",
+                property(Type::Integer, KnownFormat::Int64, "Id of the measurement"),
+            )
+            .required("id")
+            .property(
+                "measured_at",
+                property(
+                    Type::String,
+                    KnownFormat::DateTime,
+                    "When it was measured, with the offset it was measured with",
+                ),
+            )
+            .required("measured_at")
+            .property(
+                "value",
+                property(Type::Number, KnownFormat::Double, "Value in the unit"),
+            )
+            .required("value")
+            .property(
+                "unit",
+                ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .enum_values(Some([Weight::UNIT, Impedance::UNIT])),
+            )
+            .required("unit")
+            .into()
+    }
+}
+
+impl ToSchema for UserMeasurement {}
+
+/// Quotes the field when it holds a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn csv<R: Row>(rows: &[R]) -> String {
+    rows.iter().fold(R::CSV_HEADER.to_string(), |mut acc, row| {
+        acc.push('\n');
+        acc.push_str(&row.csv_row());
+        acc
+    })
+}
+
+pub fn ndjson<R: Row>(rows: &[R]) -> String {
+    rows.iter().fold(String::new(), |mut acc, row| {
+        acc.push_str(&row.json_object().to_string());
+        acc.push('\n');
+        acc
+    })
+}
+
+/// Groups the rows read from the stream into chunks.
+fn chunks<R: Row>(
+    rows: BoxStream<'static, Result<R, ApiError>>,
+) -> impl Stream<Item = Result<Vec<R>, ApiError>> + Send {
+    rows.try_chunks(CHUNK_ROWS).map_err(|error| error.1)
+}
+
+/// Same as `csv`, written chunk by chunk as the rows are read.
+pub fn csv_stream<R: Row>(
+    rows: BoxStream<'static, Result<R, ApiError>>,
+) -> BoxStream<'static, Result<String, ApiError>> {
+    Box::pin(try_stream! {
+        yield R::CSV_HEADER.to_string();
+
+        let mut chunks = Box::pin(chunks(rows));
+        while let Some(chunk) = chunks.try_next().await? {
+            yield chunk.iter().fold(String::new(), |mut acc, row| {
+                acc.push('\n');
+                acc.push_str(&row.csv_row());
+                acc
+            });
+        }
+    })
+}
+
+/// Same as `ndjson`, written chunk by chunk as the rows are read.
+pub fn ndjson_stream<R: Row>(
+    rows: BoxStream<'static, Result<R, ApiError>>,
+) -> BoxStream<'static, Result<String, ApiError>> {
+    chunks(rows).map_ok(|chunk| ndjson(&chunk)).boxed()
+}
+
+/// JSON array of the rows, written chunk by chunk as they are read.
+pub fn json_stream<R: Row>(
+    rows: BoxStream<'static, Result<R, ApiError>>,
+) -> BoxStream<'static, Result<String, ApiError>> {
+    Box::pin(try_stream! {
+        yield "[".to_string();
+
+        let mut first = true;
+        let mut chunks = Box::pin(chunks(rows));
+        while let Some(chunk) = chunks.try_next().await? {
+            let mut output = String::new();
+            for row in &chunk {
+                if !first {
+                    output.push(',');
+                }
+                first = false;
+                output.push_str(&row.json_object().to_string());
+            }
+            yield output;
+        }
+
+        yield "]".to_string();
+    })
+}
+
+/// Workbook with a single sheet for the measurements.
+pub fn workbook<T: Exportable>(measurements: &[T]) -> Result<Vec<u8>, ApiError> {
+    let mut workbook = Workbook::new();
+    write_sheet(workbook.add_worksheet(), measurements)
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    workbook
+        .save_to_buffer()
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+/// Workbook with a `Weights` and an `Impedances` sheet for the measurements that are given.
+///
+/// The time of each measurement is the local one where it was taken, its offset is kept apart.
+pub fn xlsx(
+    weights: Option<&[Weight]>,
+    impedances: Option<&[Impedance]>,
+) -> Result<Vec<u8>, ApiError> {
+    let mut workbook = Workbook::new();
+
+    if let Some(weights) = weights {
+        write_sheet(workbook.add_worksheet(), weights)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    if let Some(impedances) = impedances {
+        write_sheet(workbook.add_worksheet(), impedances)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    workbook
+        .save_to_buffer()
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+fn write_sheet<T: Exportable>(
+    worksheet: &mut Worksheet,
+    measurements: &[T],
+) -> Result<(), XlsxError> {
+    let header = Format::new().set_bold();
+    let date_time = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+
+    worksheet.set_name(T::SHEET_NAME)?;
+    worksheet.write_row_with_format(
+        0,
+        0,
+        ["id", "measured_at", "utc_offset", T::VALUE_HEADER],
+        &header,
+    )?;
+    worksheet.set_column_width(1, 20)?;
+
+    for (row, measurement) in (1..).zip(measurements) {
+        let measured_at = measurement.measured_at();
+        worksheet.write_number(row, 0, measurement.id() as f64)?;
+        worksheet.write_datetime_with_format(row, 1, measured_at.naive_local(), &date_time)?;
+        worksheet.write_string(row, 2, measured_at.offset().to_string())?;
+        worksheet.write_number(row, 3, measurement.value())?;
+    }
+
+    Ok(())
+}
+
+/// Workbook with a `Weights` and an `Impedances` sheet for the measurements of every user that
+/// are given, each one after the id and name of its user.
+pub fn users_xlsx(
+    weights: Option<&[UserMeasurement]>,
+    impedances: Option<&[UserMeasurement]>,
+) -> Result<Vec<u8>, ApiError> {
+    let mut workbook = Workbook::new();
+
+    if let Some(weights) = weights {
+        write_users_sheet::<Weight>(workbook.add_worksheet(), weights)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    if let Some(impedances) = impedances {
+        write_users_sheet::<Impedance>(workbook.add_worksheet(), impedances)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    }
+
+    workbook
+        .save_to_buffer()
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+fn write_users_sheet<T: Exportable>(
+    worksheet: &mut Worksheet,
+    measurements: &[UserMeasurement],
+) -> Result<(), XlsxError> {
+    let header = Format::new().set_bold();
+    let date_time = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+
+    worksheet.set_name(T::SHEET_NAME)?;
+    worksheet.write_row_with_format(
+        0,
+        0,
+        [
+            "user_id",
+            "user_name",
+            "id",
+            "measured_at",
+            "utc_offset",
+            T::VALUE_HEADER,
+        ],
+        &header,
+    )?;
+    worksheet.set_column_width(1, 20)?;
+    worksheet.set_column_width(3, 20)?;
+
+    for (row, measurement) in (1..).zip(measurements) {
+        let measured_at = &measurement.measured_at;
+        worksheet.write_number(row, 0, measurement.user_id as f64)?;
+        worksheet.write_string(row, 1, &*measurement.user_name)?;
+        worksheet.write_number(row, 2, measurement.id as f64)?;
+        worksheet.write_datetime_with_format(row, 3, measured_at.naive_local(), &date_time)?;
+        worksheet.write_string(row, 4, measured_at.offset().to_string())?;
+        worksheet.write_number(row, 5, measurement.value)?;
+    }
+
+    Ok(())
+}
+
+/// Parquet file with the instant of each measurement in UTC and the offset it was taken with.
+pub fn parquet<T: Exportable>(measurements: &[T]) -> Result<Vec<u8>, ApiError> {
+    parquet_file(measurements).map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+fn parquet_file<T: Exportable>(measurements: &[T]) -> Result<Vec<u8>, ParquetError> {
+    let schema = parse_message_type(&format!(
+        "message measurement {{
+            REQUIRED INT64 {};
+            REQUIRED INT64 measured_at (TIMESTAMP(MICROS, true));
+            REQUIRED INT32 utc_offset_seconds;
+            REQUIRED DOUBLE {};
+        }}",
+        T::ID_COLUMN,
+        T::VALUE_COLUMN
+    ))?;
+
+    let ids: Vec<i64> = measurements.iter().map(Exportable::id).collect();
+    let instants: Vec<i64> = measurements
+        .iter()
+        .map(|measurement| measurement.measured_at().timestamp_micros())
+        .collect();
+    let offsets: Vec<i32> = measurements
+        .iter()
+        .map(|measurement| measurement.measured_at().offset().local_minus_utc())
+        .collect();
+    let values: Vec<f64> = measurements.iter().map(Exportable::value).collect();
+
+    let mut writer = SerializedFileWriter::new(Vec::new(), Arc::new(schema), Default::default())?;
+    let mut row_group = writer.next_row_group()?;
+    for column in 0.. {
+        let Some(mut column_writer) = row_group.next_column()? else {
+            break;
+        };
+        match column {
+            0 => column_writer
+                .typed::<Int64Type>()
+                .write_batch(&ids, None, None)?,
+            1 => column_writer
+                .typed::<Int64Type>()
+                .write_batch(&instants, None, None)?,
+            2 => column_writer
+                .typed::<Int32Type>()
+                .write_batch(&offsets, None, None)?,
+            _ => column_writer
+                .typed::<DoubleType>()
+                .write_batch(&values, None, None)?,
+        };
+        column_writer.close()?;
+    }
+    row_group.close()?;
+
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        user::UserId,
+        weight::{Kilograms, WeightId},
+    };
+
+    use super::*;
+
+    fn weights() -> Vec<Weight> {
+        ["2024-01-01T20:00:00-05:00", "2024-01-02T08:00:00+01:00"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, measured_at)| Weight {
+                weight_id: WeightId::new(i as i64 + 1),
+                user_id: UserId::new(1),
+                measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+                kilograms: Kilograms::new(80.5).unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ndjson_has_a_line_per_weight_with_its_offset() {
+        let ndjson = ndjson(&weights());
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["measured_at"], "2024-01-01T20:00:00-05:00");
+        assert_eq!(lines[1]["kilograms"], 80.5);
+    }
+
+    #[tokio::test]
+    async fn streamed_json_is_the_same_array() {
+        let streamed: Vec<String> =
+            json_stream(futures::stream::iter(weights().into_iter().map(Ok)).boxed())
+                .try_collect()
+                .await
+                .unwrap();
+        let array: Vec<Value> = serde_json::from_str(&streamed.concat()).unwrap();
+
+        assert_eq!(
+            array,
+            weights().iter().map(Row::json_object).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn user_measurements_name_their_user_and_kind() {
+        let user_name: Arc<str> = "Doe, \"Jane\"".into();
+        let rows: Vec<UserMeasurement> = weights()
+            .iter()
+            .map(|weight| UserMeasurement::new(1, user_name.clone(), weight))
+            .collect();
+
+        let csv = csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], USER_MEASUREMENTS_CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "1,\"Doe, \"\"Jane\"\"\",weight,1,2024-01-01T20:00:00-05:00,80.5,kg"
+        );
+        assert_eq!(rows[1].json_object()["user_name"], "Doe, \"Jane\"");
+        assert!(users_xlsx(Some(&rows), None).unwrap().starts_with(b"PK"));
+    }
+
+    #[test]
+    fn schemas_describe_the_written_objects() {
+        let keys = |value: Value| {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let weight = &weights()[0];
+        let user_measurement = UserMeasurement::new(1, "Jane".into(), weight);
+
+        assert_eq!(
+            keys(serde_json::to_value(Weight::schema()).unwrap()["properties"].take()),
+            keys(weight.json_object())
+        );
+        assert_eq!(
+            keys(serde_json::to_value(UserMeasurement::schema()).unwrap()["properties"].take()),
+            keys(user_measurement.json_object())
+        );
+    }
+
+    #[test]
+    fn binary_exports_are_well_formed() -> Result<(), ApiError> {
+        let parquet = parquet(&weights())?;
+        assert!(parquet.starts_with(b"PAR1") && parquet.ends_with(b"PAR1"));
+
+        let xlsx = xlsx(Some(&weights()), Some(&[]))?;
+        assert!(xlsx.starts_with(b"PK"));
+
+        Ok(())
+    }
+}
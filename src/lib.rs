@@ -1,7 +1,22 @@
 pub mod app_state;
+pub mod assets;
+pub mod backup;
+pub mod charts;
+pub mod cli;
 pub mod configuration;
+pub mod digests;
 pub mod domain;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod mailer;
+pub mod mqtt;
+pub mod reminders;
+pub mod reports;
 pub mod repositories;
 pub mod routes;
+pub mod scheduler;
+pub mod statistics;
+pub mod telemetry;
 pub mod templates;
+pub mod webhooks;
@@ -0,0 +1,104 @@
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{
+    configuration::{SmtpConfiguration, SmtpTls},
+    error::ApiError,
+};
+
+/// Sends emails through the configured SMTP server.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(configuration: &SmtpConfiguration) -> Result<Self, ApiError> {
+        let builder = match configuration.tls {
+            SmtpTls::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&configuration.host)
+            }
+            SmtpTls::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&configuration.host)
+                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&configuration.host)
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+        };
+
+        let builder = match (&configuration.username, &configuration.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+
+        Ok(Mailer {
+            transport: builder.port(configuration.port).build(),
+            from: configuration
+                .from
+                .parse()
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+        })
+    }
+
+    /// Sends a plain text email.
+    pub async fn send(&self, to: &Address, subject: &str, body: String) -> Result<(), ApiError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(None, to.clone()))
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        self.deliver(message).await
+    }
+
+    /// Sends an HTML email with a plain text alternative. The PNG images are attached inline for
+    /// the HTML to show them as `cid:` followed by their content id.
+    pub async fn send_html(
+        &self,
+        to: &Address,
+        subject: &str,
+        text: String,
+        html: String,
+        images: Vec<(&str, Vec<u8>)>,
+    ) -> Result<(), ApiError> {
+        let png = ContentType::parse("image/png").map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        let related = images.into_iter().fold(
+            MultiPart::related().singlepart(SinglePart::html(html)),
+            |related, (content_id, image)| {
+                related.singlepart(
+                    Attachment::new_inline(content_id.to_string()).body(image, png.clone()),
+                )
+            },
+        );
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(None, to.clone()))
+            .subject(subject)
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(text))
+                    .multipart(related),
+            )
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        self.deliver(message).await
+    }
+
+    async fn deliver(&self, message: Message) -> Result<(), ApiError> {
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono_tz::Tz;
+use rumqttc::{AsyncClient, EventLoop, MqttOptions, QoS};
+use serde_json::{json, Value};
+
+use crate::{
+    configuration::MqttConfiguration,
+    domain::user::{User, UserId},
+    error::ApiError,
+    repositories::Repository,
+    statistics,
+};
+
+/// Publishes the latest derived values of each user to MQTT together with the
+/// Home Assistant discovery configuration so they show up as sensors.
+#[derive(Clone)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    topic_prefix: String,
+    discovery_prefix: String,
+    /// Timezone of the users without their own.
+    timezone: Tz,
+}
+
+struct Sensor {
+    object_id: &'static str,
+    name: &'static str,
+    unit_of_measurement: &'static str,
+    device_class: Option<&'static str>,
+    icon: &'static str,
+}
+
+const SENSORS: [Sensor; 3] = [
+    Sensor {
+        object_id: "weight",
+        name: "Weight",
+        unit_of_measurement: "kg",
+        device_class: Some("weight"),
+        icon: "mdi:scale-bathroom",
+    },
+    Sensor {
+        object_id: "bmi",
+        name: "BMI",
+        unit_of_measurement: "kg/m²",
+        device_class: None,
+        icon: "mdi:human",
+    },
+    Sensor {
+        object_id: "trend",
+        name: "Trend",
+        unit_of_measurement: "kg/d",
+        device_class: None,
+        icon: "mdi:trending-neutral",
+    },
+];
+
+impl MqttPublisher {
+    pub fn connect(configuration: &MqttConfiguration, timezone: Tz) -> MqttPublisher {
+        let mut options = MqttOptions::new(
+            &configuration.client_id,
+            &configuration.host,
+            configuration.port,
+        );
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &configuration.username {
+            options.set_credentials(username, configuration.password.clone().unwrap_or_default());
+        }
+
+        let (client, event_loop) = AsyncClient::new(options, 10);
+        tokio::spawn(poll(event_loop));
+
+        MqttPublisher {
+            client,
+            topic_prefix: configuration.topic_prefix.clone(),
+            discovery_prefix: configuration.discovery_prefix.clone(),
+            timezone,
+        }
+    }
+
+    pub async fn publish_all_users(&self, repository: &dyn Repository) -> Result<(), ApiError> {
+        let users = repository.find_users().await?;
+
+        for user in users {
+            self.publish(repository, &user).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn publish_user(
+        &self,
+        repository: &dyn Repository,
+        user_id: &UserId,
+    ) -> Result<(), ApiError> {
+        let user = repository
+            .find_user(user_id)
+            .await?
+            .ok_or(ApiError::UserNotFound)?;
+
+        self.publish(repository, &user).await
+    }
+
+    /// Publishes the user without making the caller wait for the broker.
+    pub fn publish_user_in_background(&self, repository: Arc<dyn Repository>, user_id: UserId) {
+        let publisher = self.clone();
+        tokio::spawn(async move {
+            if let Err(error) = publisher.publish_user(&*repository, &user_id).await {
+                tracing::warn!(?error, "Failed to publish user to MQTT");
+            }
+        });
+    }
+
+    /// Clears the retained discovery configurations and state of a deleted user, so that Home
+    /// Assistant removes its sensors, without making the caller wait for the broker.
+    pub fn unpublish_user_in_background(&self, user_id: UserId) {
+        let publisher = self.clone();
+        tokio::spawn(async move {
+            if let Err(error) = publisher.unpublish(&user_id).await {
+                tracing::warn!(?error, "Failed to unpublish user from MQTT");
+            }
+        });
+    }
+
+    async fn publish(&self, repository: &dyn Repository, user: &User) -> Result<(), ApiError> {
+        let state_topic = self.state_topic(&user.id);
+
+        for sensor in SENSORS.iter() {
+            let config = discovery_config(user, sensor, &state_topic);
+            self.send(self.discovery_topic(&user.id, sensor), config.to_string())
+                .await?;
+        }
+
+        let state = self.state(repository, user).await?;
+        self.send(state_topic, state.to_string()).await
+    }
+
+    async fn unpublish(&self, user_id: &UserId) -> Result<(), ApiError> {
+        // An empty retained message deletes the retained one
+        for sensor in SENSORS.iter() {
+            self.send(self.discovery_topic(user_id, sensor), String::new())
+                .await?;
+        }
+
+        self.send(self.state_topic(user_id), String::new()).await
+    }
+
+    fn state_topic(&self, user_id: &UserId) -> String {
+        format!("{}/users/{}/state", self.topic_prefix, i64::from(user_id))
+    }
+
+    fn discovery_topic(&self, user_id: &UserId, sensor: &Sensor) -> String {
+        format!(
+            "{}/sensor/weight_tracker_{}/{}/config",
+            self.discovery_prefix,
+            i64::from(user_id),
+            sensor.object_id
+        )
+    }
+
+    async fn state(&self, repository: &dyn Repository, user: &User) -> Result<Value, ApiError> {
+        let Some(reading) = statistics::latest_reading(repository, user, self.timezone).await?
+        else {
+            return Ok(json!({ "weight": null, "bmi": null, "trend": null, "measured_at": null }));
+        };
+
+        let kilograms: f64 = reading.weight.kilograms.into();
+
+        Ok(json!({
+            "weight": kilograms,
+            "bmi": reading.bmi.map(|bmi| round(bmi, 1)),
+            "trend": reading.trend.map(|trend| round(trend, 3)),
+            "measured_at": reading.weight.measured_at.to_rfc3339(),
+        }))
+    }
+
+    async fn send(&self, topic: String, payload: String) -> Result<(), ApiError> {
+        self.client
+            .publish(topic, QoS::AtLeastOnce, true, payload)
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+}
+
+fn discovery_config(user: &User, sensor: &Sensor, state_topic: &str) -> Value {
+    let user_id: i64 = (&user.id).into();
+    let user_name: String = (&user.name).into();
+
+    let mut config = json!({
+        "name": sensor.name,
+        "unique_id": format!("weight_tracker_{}_{}", user_id, sensor.object_id),
+        "state_topic": state_topic,
+        "value_template": format!("{{{{ value_json.{} }}}}", sensor.object_id),
+        "unit_of_measurement": sensor.unit_of_measurement,
+        "state_class": "measurement",
+        "icon": sensor.icon,
+        "device": {
+            "identifiers": [format!("weight_tracker_{}", user_id)],
+            "name": format!("Weight Tracker {}", user_name),
+            "manufacturer": "Weight Tracker",
+        },
+    });
+
+    if let Some(device_class) = sensor.device_class {
+        config["device_class"] = json!(device_class);
+    }
+
+    config
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10_f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// Drives the connection to the broker, rumqttc reconnects on the next poll after an error.
+async fn poll(mut event_loop: EventLoop) {
+    loop {
+        if let Err(error) = event_loop.poll().await {
+            tracing::warn!(%error, "MQTT connection error");
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+
+use crate::{
+    configuration::RemindersConfiguration,
+    domain::{reminder::ReminderSettings, webhook::WebhookEvent},
+    error::ApiError,
+    mailer::Mailer,
+    repositories::Repository,
+    scheduler,
+    webhooks::Webhooks,
+};
+
+const SUBJECT: &str = "Time to weigh in";
+
+/// Reminds the users that have not weighed in for a while through the channels each one chose.
+#[derive(Clone)]
+pub struct Reminders {
+    repository: Arc<dyn Repository>,
+    configuration: RemindersConfiguration,
+    mailer: Option<Mailer>,
+    webhooks: Webhooks,
+    client: reqwest::Client,
+    timezone: Tz,
+}
+
+impl Reminders {
+    /// `timezone` is the one of the users without their own, for their quiet hours.
+    pub fn new(
+        repository: Arc<dyn Repository>,
+        configuration: RemindersConfiguration,
+        mailer: Option<Mailer>,
+        webhooks: Webhooks,
+        timezone: Tz,
+    ) -> Result<Self, ApiError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(Reminders {
+            repository,
+            configuration,
+            mailer,
+            webhooks,
+            client,
+            timezone,
+        })
+    }
+
+    /// Checks which reminders are due every configured interval.
+    pub fn schedule(self) {
+        let period = Duration::from_secs(self.configuration.check_interval_minutes * 60);
+        let reminders = Arc::new(self);
+
+        scheduler::every(period, "Failed to check the reminders", move |now| {
+            let reminders = reminders.clone();
+            async move { reminders.remind_all(&now).await }
+        });
+    }
+
+    /// Sends the reminders due at the time, a failure with one user does not stop the others.
+    pub async fn remind_all(&self, now: &DateTime<Utc>) -> Result<(), ApiError> {
+        for settings in self.repository.find_all_reminder_settings().await? {
+            if let Err(error) = self.remind(&settings, now).await {
+                let user_id: i64 = (&settings.user_id).into();
+                tracing::warn!(?error, user_id, "Failed to remind the user");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the reminder of the user was due and delivered through at least one channel.
+    async fn remind(
+        &self,
+        settings: &ReminderSettings,
+        now: &DateTime<Utc>,
+    ) -> Result<bool, ApiError> {
+        let Some(user) = self.repository.find_user(&settings.user_id).await? else {
+            return Ok(false);
+        };
+        // Nothing to compare with until the first weigh-in
+        let Some(latest) = self.repository.find_latest_weight(&user.id).await? else {
+            return Ok(false);
+        };
+
+        let timezone = user.timezone_or(self.timezone);
+        let Some(days) = settings.due(
+            self.configuration.after_days,
+            &latest.measured_at,
+            now,
+            timezone,
+        ) else {
+            return Ok(false);
+        };
+
+        let user_id: i64 = (&user.id).into();
+        let name: String = user.name.into();
+        let kilograms: f64 = latest.kilograms.into();
+        let message = format!(
+            "Hi {}, you have not weighed in for {} days, since {} when you weighed {} kg.",
+            name,
+            days,
+            latest
+                .measured_at
+                .with_timezone(&timezone)
+                .format("%Y-%m-%d"),
+            kilograms
+        );
+
+        // Retried on the next check unless at least one channel took it
+        let mut delivered = false;
+
+        if let Some(email) = &settings.email {
+            match &self.mailer {
+                Some(mailer) => match mailer.send(email, SUBJECT, message.clone()).await {
+                    Ok(()) => delivered = true,
+                    Err(error) => tracing::warn!(?error, user_id, "Failed to email the reminder"),
+                },
+                None => tracing::warn!(user_id, "Reminder not emailed, SMTP is not configured"),
+            }
+        }
+
+        if let Some(topic) = &settings.ntfy_topic {
+            match self.push(topic, &message).await {
+                Ok(()) => delivered = true,
+                Err(error) => {
+                    tracing::warn!(?error, user_id, "Failed to push the reminder to ntfy")
+                }
+            }
+        }
+
+        // Only a webhook that took the first attempt counts, the retries of the others could come
+        // after the next check
+        let notified = self
+            .webhooks
+            .notify_first_attempt(
+                WebhookEvent::ReminderDue,
+                user_id,
+                json!({
+                    "user_id": user_id,
+                    "days_without_weighing_in": days,
+                    "last_measured_at": latest.measured_at,
+                    "last_kilograms": kilograms,
+                }),
+            )
+            .await;
+        if notified > 0 {
+            delivered = true;
+        }
+
+        if !delivered {
+            return Ok(false);
+        }
+        self.repository
+            .update_last_reminded_at(&settings.user_id, now)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Publishes the message to the topic of the ntfy server.
+    async fn push(&self, topic: &str, message: &str) -> Result<(), ApiError> {
+        let url = format!(
+            "{}/{}",
+            self.configuration.ntfy_url.trim_end_matches('/'),
+            topic
+        );
+
+        let mut request = self
+            .client
+            .post(url)
+            .header("Title", SUBJECT)
+            .header("Tags", "scales")
+            .body(message.to_string());
+        if let Some(token) = &self.configuration.ntfy_token {
+            request = request.bearer_auth(token);
+        }
+
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::{
+        extract::{Path, State},
+        http::HeaderMap,
+        routing::post,
+        Router,
+    };
+    use chrono::DateTime;
+
+    use crate::{
+        configuration::{DatabaseConfiguration, WebhooksConfiguration},
+        domain::{
+            user::{UserId, UserName},
+            webhook::WebhookUrl,
+            weight::Kilograms,
+        },
+        repositories::sqlite::SqliteRepository,
+    };
+
+    use super::*;
+
+    type Pushed = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// Stand-in for a ntfy server, keeping the topic, title and message of every push.
+    async fn ntfy() -> (Pushed, String) {
+        async fn receive(
+            State(pushed): State<Pushed>,
+            Path(topic): Path<String>,
+            headers: HeaderMap,
+            body: String,
+        ) {
+            let title = headers["Title"].to_str().unwrap().to_string();
+            pushed.lock().unwrap().push((topic, title, body));
+        }
+
+        let pushed = Pushed::default();
+        let router = Router::new()
+            .route("/{topic}", post(receive))
+            .with_state(pushed.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        (pushed, url)
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().to_utc()
+    }
+
+    /// Reminders of Ana, who last weighed in on 2026-10-15 and wants a push to ntfy.
+    async fn reminders(ntfy_url: String) -> (Reminders, Arc<dyn Repository>) {
+        let repository: Arc<dyn Repository> = Arc::new(
+            SqliteRepository::connect(&DatabaseConfiguration {
+                url: "sqlite::memory:".to_string(),
+                max_connections: 1,
+            })
+            .await
+            .unwrap(),
+        );
+        repository.run_migrations().await.unwrap();
+        repository
+            .insert_user(&UserName::new("Ana".to_string()), None, None, None)
+            .await
+            .unwrap();
+        let user_id = UserId::new(1);
+        repository
+            .insert_weight(
+                &user_id,
+                &DateTime::parse_from_rfc3339("2026-10-15T08:00:00+02:00").unwrap(),
+                &Kilograms::new(71.5).unwrap(),
+            )
+            .await
+            .unwrap();
+        repository
+            .save_reminder_settings(&ReminderSettings {
+                user_id: UserId::new(1),
+                after_days: None,
+                email: None,
+                ntfy_topic: Some("ana-weighs-in".to_string()),
+                quiet_hours: None,
+                last_reminded_at: None,
+            })
+            .await
+            .unwrap();
+
+        let webhooks =
+            Webhooks::new(repository.clone(), &WebhooksConfiguration::default()).unwrap();
+        let reminders = Reminders::new(
+            repository.clone(),
+            RemindersConfiguration {
+                ntfy_url,
+                ..Default::default()
+            },
+            None,
+            webhooks,
+            Tz::Europe__Madrid,
+        )
+        .unwrap();
+
+        (reminders, repository)
+    }
+
+    #[tokio::test]
+    async fn due_reminders_are_pushed_once_a_day() {
+        let (pushed, ntfy_url) = ntfy().await;
+        let (reminders, _) = reminders(ntfy_url).await;
+
+        reminders
+            .remind_all(&at("2026-10-16T12:00:00Z"))
+            .await
+            .unwrap();
+        reminders
+            .remind_all(&at("2026-10-18T12:00:00Z"))
+            .await
+            .unwrap();
+        reminders
+            .remind_all(&at("2026-10-18T20:00:00Z"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *pushed.lock().unwrap(),
+            vec![(
+                "ana-weighs-in".to_string(),
+                SUBJECT.to_string(),
+                "Hi Ana, you have not weighed in for 3 days, since 2026-10-15 when you weighed 71.5 kg."
+                    .to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn undelivered_reminders_are_not_recorded() {
+        // Nothing listens on the port once the listener is dropped
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ntfy_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let (reminders, repository) = reminders(ntfy_url).await;
+
+        reminders
+            .remind_all(&at("2026-10-18T12:00:00Z"))
+            .await
+            .unwrap();
+
+        let settings = repository
+            .find_reminder_settings(&UserId::new(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings.last_reminded_at, None);
+    }
+
+    #[tokio::test]
+    async fn reminders_are_not_recorded_before_a_webhook_took_them() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let (reminders, repository) = reminders(url.clone()).await;
+        // The webhook is the only channel left
+        repository
+            .save_reminder_settings(&ReminderSettings {
+                user_id: UserId::new(1),
+                after_days: None,
+                email: None,
+                ntfy_topic: None,
+                quiet_hours: None,
+                last_reminded_at: None,
+            })
+            .await
+            .unwrap();
+        repository
+            .insert_webhook(
+                None,
+                &WebhookUrl::new(&url).unwrap(),
+                "secret",
+                &[WebhookEvent::ReminderDue],
+                &Utc::now(),
+            )
+            .await
+            .unwrap();
+
+        reminders
+            .remind_all(&at("2026-10-18T12:00:00Z"))
+            .await
+            .unwrap();
+
+        let settings = repository
+            .find_reminder_settings(&UserId::new(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings.last_reminded_at, None);
+    }
+}
@@ -0,0 +1,493 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use chrono_tz::Tz;
+use printpdf::{
+    BuiltinFont, Color, ColorBits, ColorSpace, Image, ImageTransform, ImageXObject,
+    IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Px,
+    Rgb,
+};
+
+use crate::{
+    charts::WeightChart,
+    domain::{
+        impedance::Impedance,
+        report::{monthly_summaries, BodyComposition, WeightSummary},
+        user::User,
+        weight::Weight,
+    },
+    error::ApiError,
+};
+
+/// A4 in portrait.
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+/// The chart takes the whole width between the margins, rasterized twice as large as drawn so
+/// that it stays sharp on paper.
+const CHART_WIDTH: u32 = 680;
+const CHART_HEIGHT: u32 = 300;
+const CHART_SCALE: u32 = 2;
+/// Points of the text sizes.
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 13.0;
+const TEXT_SIZE: f32 = 10.0;
+const SMALL_SIZE: f32 = 8.5;
+/// Left edge of the columns of the monthly summary, in millimeters from the margin.
+const COLUMNS: [f32; 6] = [0.0, 45.0, 70.0, 95.0, 120.0, 145.0];
+/// Characters of the notes per line, about the width between the margins in Helvetica.
+const NOTES_LINE_LENGTH: usize = 95;
+/// Longest notes accepted, the report is meant to be a printout and not a diary.
+pub const MAX_NOTES_LENGTH: usize = 2000;
+
+/// Printable summary of the weights of a user over a range of dates, for doctors and others that
+/// ask for one on paper.
+pub struct Report<'a> {
+    pub user: &'a User,
+    /// Oldest first, as are the impedances.
+    pub weights: &'a [Weight],
+    pub impedances: &'a [Impedance],
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    /// Timezone of the days and months the measurements are grouped in.
+    pub timezone: Tz,
+    pub notes: Option<&'a str>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Report<'_> {
+    /// Only the characters of Windows-1252 are printed, the fonts are the ones built in every PDF
+    /// reader so that nothing has to be installed on the server.
+    pub fn pdf(&self) -> Result<Vec<u8>, ApiError> {
+        let name: String = (&self.user.name).into();
+        let mut writer = Writer::new(&format!("Weight report of {}", name))?;
+
+        writer.text("Weight report", TITLE_SIZE, true);
+        writer.text(
+            &format!(
+                "{}, from {} to {}",
+                name,
+                date(&self.start, &self.timezone),
+                date(&self.end, &self.timezone)
+            ),
+            TEXT_SIZE,
+            false,
+        );
+        writer.text(
+            &format!(
+                "Generated on {} ({})",
+                self.generated_at
+                    .with_timezone(&self.timezone)
+                    .format("%-d %b %Y at %H:%M"),
+                self.timezone
+            ),
+            SMALL_SIZE,
+            false,
+        );
+
+        self.write_profile(&mut writer);
+        self.write_weights(&mut writer)?;
+        self.write_months(&mut writer);
+        self.write_body_composition(&mut writer);
+        if let Some(notes) = self.notes.filter(|notes| !notes.trim().is_empty()) {
+            let lines = wrap(notes, NOTES_LINE_LENGTH);
+            writer.heading("Notes", lines.len().min(3));
+            for line in lines {
+                writer.text(&line, TEXT_SIZE, false);
+            }
+        }
+
+        writer.finish()
+    }
+
+    fn write_profile(&self, writer: &mut Writer) {
+        writer.heading("Profile", 4);
+        writer.field("Name", &String::from(&self.user.name));
+        writer.field(
+            "Height",
+            &self
+                .user
+                .height
+                .as_ref()
+                .map_or("Not set".to_string(), |height| {
+                    format!("{:.0} cm", f64::from(height))
+                }),
+        );
+        writer.field("Timezone", &self.timezone.to_string());
+        writer.field(
+            "Goal",
+            &self
+                .user
+                .goal
+                .as_ref()
+                .map_or("Not set".to_string(), |goal| kilograms(goal.into())),
+        );
+    }
+
+    fn write_weights(&self, writer: &mut Writer) -> Result<(), ApiError> {
+        writer.heading("Weights", 9);
+        let Some(summary) =
+            WeightSummary::new(self.weights, self.user.height.as_ref(), &self.timezone)
+        else {
+            writer.text("No weights between these dates.", TEXT_SIZE, false);
+            return Ok(());
+        };
+
+        writer.field("Weigh-ins", &summary.weigh_ins.to_string());
+        writer.field("First weight", &kilograms(summary.first));
+        writer.field("Latest weight", &kilograms(summary.latest));
+        writer.field("Change", &difference(summary.latest - summary.first));
+        writer.field("Lowest", &kilograms(summary.minimum));
+        writer.field("Highest", &kilograms(summary.maximum));
+        if let Some(trend) = summary.trend {
+            writer.field("Trend", &format!("{}/week", difference(trend)));
+        }
+        if let Some(bmi) = summary.bmi {
+            writer.field("Latest BMI", &format!("{:.1}", bmi));
+        }
+        if let Some(goal) = &self.user.goal {
+            writer.field(
+                "Left to the goal",
+                &kilograms((summary.latest - f64::from(goal)).abs()),
+            );
+        }
+
+        let chart = WeightChart {
+            weights: self.weights,
+            goal: self.user.goal.as_ref().map(f64::from),
+            start: self.start,
+            end: self.end,
+            timezone: self.timezone,
+            width: CHART_WIDTH,
+            height: CHART_HEIGHT,
+        };
+        writer.image(
+            chart.rgb(CHART_SCALE)?,
+            CHART_WIDTH * CHART_SCALE,
+            CHART_HEIGHT * CHART_SCALE,
+        );
+
+        Ok(())
+    }
+
+    fn write_months(&self, writer: &mut Writer) {
+        let months = monthly_summaries(self.weights, &self.timezone);
+        if months.is_empty() {
+            return;
+        }
+
+        writer.heading("Monthly summary", (months.len() + 1).min(6));
+        writer.row(
+            &[
+                "Month",
+                "Weigh-ins",
+                "Average",
+                "Lowest",
+                "Highest",
+                "Change",
+            ],
+            true,
+        );
+        for month in months {
+            let name = NaiveDate::from_ymd_opt(month.year, month.month, 1)
+                .map(|day| day.format("%B %Y").to_string())
+                .unwrap_or_default();
+            writer.row(
+                &[
+                    &name,
+                    &month.weigh_ins.to_string(),
+                    &kilograms(month.average),
+                    &kilograms(month.minimum),
+                    &kilograms(month.maximum),
+                    &difference(month.change),
+                ],
+                false,
+            );
+        }
+    }
+
+    fn write_body_composition(&self, writer: &mut Writer) {
+        let Some(composition) = BodyComposition::new(self.impedances, self.user.height.as_ref())
+        else {
+            return;
+        };
+
+        writer.heading("Body composition", 4);
+        writer.field("Measurements", &composition.measurements.to_string());
+        writer.field(
+            "Latest impedance",
+            &format!(
+                "{:.0} ohm on {}",
+                composition.latest_ohms,
+                date(&composition.latest_measured_at, &self.timezone)
+            ),
+        );
+        writer.field(
+            "Average impedance",
+            &format!("{:.0} ohm", composition.average_ohms),
+        );
+        if let Some(index) = composition.impedance_index {
+            writer.field("Impedance index", &format!("{:.1} cm\u{b2}/ohm", index));
+        }
+    }
+}
+
+/// Lays out the report from the top of the first page down, adding pages as they fill up.
+struct Writer {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Millimeters from the bottom of the page to the top of the next line.
+    y: f32,
+}
+
+impl Writer {
+    fn new(title: &str) -> Result<Self, ApiError> {
+        let (document, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        let regular = document
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        let bold = document
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        let layer = document.get_page(page).get_layer(layer);
+
+        Ok(Writer {
+            document,
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    /// Starts a new page unless the height fits in what is left of the current one.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+
+        let (page, layer) = self
+            .document
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn font(&self, bold: bool) -> &IndirectFontRef {
+        if bold {
+            &self.bold
+        } else {
+            &self.regular
+        }
+    }
+
+    fn text(&mut self, text: &str, size: f32, bold: bool) {
+        self.row_at(&[(0.0, text)], size, bold);
+    }
+
+    /// Section title with a rule under it, on a new page unless the first lines of the section
+    /// fit under it, which keeps short sections in one piece.
+    fn heading(&mut self, text: &str, lines: usize) {
+        self.reserve(6.0 + line_height(HEADING_SIZE) + line_height(TEXT_SIZE) * lines as f32);
+        self.y -= 4.0;
+        self.text(text, HEADING_SIZE, true);
+
+        self.layer
+            .set_outline_color(Color::Rgb(Rgb::new(0.8, 0.8, 0.8, None)));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y + 1.0)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y + 1.0)), false),
+            ],
+            is_closed: false,
+        });
+        self.y -= 2.0;
+    }
+
+    /// Label and value of a section.
+    fn field(&mut self, label: &str, value: &str) {
+        self.reserve(line_height(TEXT_SIZE));
+        let y = self.y - pt_to_mm(TEXT_SIZE);
+        self.layer
+            .use_text(label, TEXT_SIZE, Mm(MARGIN), Mm(y), &self.bold);
+        self.layer.use_text(
+            value,
+            TEXT_SIZE,
+            Mm(MARGIN + COLUMNS[2]),
+            Mm(y),
+            &self.regular,
+        );
+        self.y -= line_height(TEXT_SIZE);
+    }
+
+    /// Row of the table, one cell per column.
+    fn row(&mut self, cells: &[&str], bold: bool) {
+        let cells: Vec<(f32, &str)> = COLUMNS.iter().copied().zip(cells.iter().copied()).collect();
+        self.row_at(&cells, TEXT_SIZE, bold);
+    }
+
+    fn row_at(&mut self, cells: &[(f32, &str)], size: f32, bold: bool) {
+        self.reserve(line_height(size));
+        let y = self.y - pt_to_mm(size);
+        for (x, text) in cells {
+            self.layer
+                .use_text(*text, size, Mm(MARGIN + x), Mm(y), self.font(bold));
+        }
+        self.y -= line_height(size);
+    }
+
+    /// RGB image as wide as the space between the margins.
+    fn image(&mut self, rgb: Vec<u8>, width: u32, height: u32) {
+        let width_mm = PAGE_WIDTH - 2.0 * MARGIN;
+        let height_mm = width_mm * height as f32 / width as f32;
+        // Dots per inch that make the image as wide as wanted
+        let dpi = width as f32 / (width_mm / 25.4);
+
+        self.reserve(height_mm + 4.0);
+        self.y -= 4.0;
+        Image::from(ImageXObject {
+            width: Px(width as usize),
+            height: Px(height as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: rgb,
+            image_filter: None,
+            smask: None,
+            clipping_bbox: None,
+        })
+        .add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN)),
+                translate_y: Some(Mm(self.y - height_mm)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+        self.y -= height_mm;
+    }
+
+    fn finish(self) -> Result<Vec<u8>, ApiError> {
+        // The layer refers to the document, which cannot be taken while it does
+        drop(self.layer);
+        self.document
+            .save_to_bytes()
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+}
+
+fn pt_to_mm(points: f32) -> f32 {
+    points * 25.4 / 72.0
+}
+
+fn line_height(size: f32) -> f32 {
+    pt_to_mm(size) * 1.5
+}
+
+fn date(instant: &DateTime<FixedOffset>, timezone: &Tz) -> String {
+    instant
+        .with_timezone(timezone)
+        .format("%-d %b %Y")
+        .to_string()
+}
+
+fn kilograms(value: f64) -> String {
+    format!("{:.1} kg", value)
+}
+
+fn difference(value: f64) -> String {
+    format!("{:+.1} kg", value)
+}
+
+/// Splits the text in lines of up to the length, breaking between words and keeping the line
+/// breaks it already has.
+fn wrap(text: &str, length: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > length {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        impedance::{ImpedanceId, Ohms},
+        user::{Centimeters, UserId, UserName},
+        weight::{Kilograms, WeightId},
+    };
+
+    use super::*;
+
+    fn weight(measured_at: &str, kilograms: f64) -> Weight {
+        Weight {
+            weight_id: WeightId::new(1),
+            user_id: UserId::new(1),
+            measured_at: DateTime::parse_from_rfc3339(measured_at).unwrap(),
+            kilograms: Kilograms::new(kilograms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn notes_are_wrapped_between_words() {
+        assert_eq!(
+            wrap("Started the new diet\non Monday", 12),
+            vec!["Started the", "new diet", "on Monday"]
+        );
+    }
+
+    #[test]
+    fn reports_are_paged_pdf_documents() {
+        let user = User {
+            id: UserId::new(1),
+            name: UserName::new("Ana".to_string()),
+            height: Some(Centimeters::new(170.0).unwrap()),
+            timezone: Some(Tz::Europe__Madrid),
+            goal: Some(Kilograms::new(75.0).unwrap()),
+        };
+        let weights = [
+            weight("2026-09-01T08:00:00+02:00", 81.0),
+            weight("2026-09-20T08:00:00+02:00", 80.0),
+            weight("2026-10-12T08:00:00+02:00", 79.0),
+        ];
+        let impedances = [Impedance {
+            impedance_id: ImpedanceId::new(1),
+            user_id: UserId::new(1),
+            measured_at: DateTime::parse_from_rfc3339("2026-10-12T08:00:00+02:00").unwrap(),
+            ohms: Ohms::new(500.0).unwrap(),
+        }];
+        let notes = "Walking an hour every day. ".repeat(MAX_NOTES_LENGTH / 27);
+
+        let pdf = Report {
+            user: &user,
+            weights: &weights,
+            impedances: &impedances,
+            start: DateTime::parse_from_rfc3339("2026-09-01T00:00:00+02:00").unwrap(),
+            end: DateTime::parse_from_rfc3339("2026-10-19T00:00:00+02:00").unwrap(),
+            timezone: Tz::Europe__Madrid,
+            notes: Some(&notes),
+            generated_at: Utc::now(),
+        }
+        .pdf()
+        .unwrap();
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        let pages = printpdf::lopdf::Document::load_mem(&pdf)
+            .unwrap()
+            .get_pages();
+        assert_eq!(pages.len(), 2);
+    }
+}
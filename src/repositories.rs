@@ -1,2 +1,53 @@
+use std::sync::Arc;
+
+use crate::configuration::{DatabaseBackend, DatabaseConfiguration};
+
+use self::{
+    digests::DigestRepository, measurements::MeasurementRepository, postgres::PostgresRepository,
+    reminders::ReminderRepository, schema::SchemaRepository, sqlite::SqliteRepository,
+    users::UserRepository, webhooks::WebhookRepository,
+};
+
+pub mod digests;
 pub mod measurements;
+pub mod postgres;
+pub mod reminders;
+pub mod schema;
+pub mod sqlite;
 pub mod users;
+pub mod webhooks;
+
+/// Everything the service needs from the database, regardless of the backend storing it.
+pub trait Repository:
+    UserRepository
+    + MeasurementRepository
+    + WebhookRepository
+    + ReminderRepository
+    + DigestRepository
+    + SchemaRepository
+{
+}
+
+impl<T> Repository for T where
+    T: UserRepository
+        + MeasurementRepository
+        + WebhookRepository
+        + ReminderRepository
+        + DigestRepository
+        + SchemaRepository
+{
+}
+
+/// Opens a connection pool to the backend selected by the scheme of the database URL.
+pub async fn connect(
+    configuration: &DatabaseConfiguration,
+) -> Result<Arc<dyn Repository>, sqlx::Error> {
+    match configuration.backend() {
+        Some(DatabaseBackend::Postgres) => {
+            Ok(Arc::new(PostgresRepository::connect(configuration).await?))
+        }
+        Some(DatabaseBackend::Sqlite) | None => {
+            Ok(Arc::new(SqliteRepository::connect(configuration).await?))
+        }
+    }
+}
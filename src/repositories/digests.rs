@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{digest::DigestSettings, user::UserId},
+    error::ApiError,
+};
+
+#[async_trait]
+pub trait DigestRepository: Send + Sync {
+    async fn find_digest_settings(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Option<DigestSettings>, ApiError>;
+
+    /// Settings of every user that wants a digest.
+    async fn find_all_digest_settings(&self) -> Result<Vec<DigestSettings>, ApiError>;
+
+    /// Creates or replaces the settings of the user, when its last digest was sent is kept.
+    async fn save_digest_settings(&self, settings: &DigestSettings) -> Result<(), ApiError>;
+
+    /// Fails with `DigestNotFound` when the user had none.
+    async fn delete_digest_settings(&self, user_id: &UserId) -> Result<(), ApiError>;
+
+    async fn update_digest_sent_at(
+        &self,
+        user_id: &UserId,
+        sent_at: &DateTime<Utc>,
+    ) -> Result<(), ApiError>;
+}
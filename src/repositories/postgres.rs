@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnectOptions, PgPoolOptions},
+    Pool, Postgres,
+};
+
+use crate::configuration::DatabaseConfiguration;
+
+// The offline query metadata of both backends shares the `.sqlx` directory and it is keyed by the
+// text of the query, so queries here must never be written exactly as their SQLite counterparts.
+// Explicit casts of the parameters are used for that where the SQL would otherwise be the same.
+mod digests;
+mod measurements;
+mod reminders;
+mod schema;
+mod users;
+mod webhooks;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+pub struct PostgresRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresRepository {
+    /// Opens a connection pool to an existing database, it is never created.
+    pub async fn connect(configuration: &DatabaseConfiguration) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(configuration.max_connections)
+            .connect_with(
+                // Keeps notices such as the ones of `CREATE TABLE IF NOT EXISTS` out of the logs
+                PgConnectOptions::from_str(&configuration.url)?
+                    .options([("client_min_messages", "warning")]),
+            )
+            .await?;
+
+        Ok(PostgresRepository { pool })
+    }
+}
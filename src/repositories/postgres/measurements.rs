@@ -0,0 +1,605 @@
+use std::time::Instant;
+
+use async_stream::try_stream;
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::{stream::BoxStream, TryStreamExt};
+
+use crate::{
+    domain::{
+        impedance::{Impedance, ImpedanceId, Ohms},
+        page::{PageRequest, SortOrder},
+        user::UserId,
+        weight::{Kilograms, Weight, WeightId},
+    },
+    error::ApiError,
+    repositories::measurements::MeasurementRepository,
+    telemetry,
+};
+
+use super::PostgresRepository;
+
+#[async_trait]
+impl MeasurementRepository for PostgresRepository {
+    async fn insert_weight(
+        &self,
+        user_id: &UserId,
+        measured_at: &DateTime<FixedOffset>,
+        kilograms: &Kilograms,
+    ) -> Result<WeightId, ApiError> {
+        let user_id: i64 = user_id.into();
+        let kilograms: f64 = kilograms.into();
+        let utc_offset_seconds = measured_at.offset().local_minus_utc();
+
+        let weight_id = telemetry::time_query(
+            "insert_weight",
+            sqlx::query_scalar!(
+                r#"INSERT INTO weight (user_id, measured_at, utc_offset_seconds, kilograms) VALUES ($1, $2::TIMESTAMPTZ, $3, $4) RETURNING weight_id"#,
+                user_id,
+                measured_at,
+                utc_offset_seconds,
+                kilograms
+            )
+            .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(WeightId::new(weight_id))
+    }
+
+    async fn insert_impedance(
+        &self,
+        user_id: &UserId,
+        measured_at: &DateTime<FixedOffset>,
+        ohms: &Ohms,
+    ) -> Result<ImpedanceId, ApiError> {
+        let user_id: i64 = user_id.into();
+        let ohms: f64 = ohms.into();
+        let utc_offset_seconds = measured_at.offset().local_minus_utc();
+
+        let impedance_id = telemetry::time_query(
+            "insert_impedance",
+            sqlx::query_scalar!(
+                r#"INSERT INTO impedance (user_id, measured_at, utc_offset_seconds, ohms) VALUES ($1, $2::TIMESTAMPTZ, $3, $4) RETURNING impedance_id"#,
+                user_id,
+                measured_at,
+                utc_offset_seconds,
+                ohms
+            )
+            .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(ImpedanceId::new(impedance_id))
+    }
+
+    async fn insert_weights(
+        &self,
+        user_id: &UserId,
+        weights: &[(DateTime<FixedOffset>, Kilograms)],
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        for (measured_at, kilograms) in weights {
+            let utc_offset_seconds = measured_at.offset().local_minus_utc();
+            let kilograms: f64 = kilograms.into();
+
+            telemetry::time_query(
+                "insert_weight",
+                sqlx::query_scalar!(
+                    r#"INSERT INTO weight (user_id, measured_at, utc_offset_seconds, kilograms) VALUES ($1, $2::TIMESTAMPTZ, $3, $4) RETURNING weight_id"#,
+                    user_id,
+                    measured_at,
+                    utc_offset_seconds,
+                    kilograms
+                )
+                .fetch_one(&mut *transaction),
+            )
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    async fn insert_impedances(
+        &self,
+        user_id: &UserId,
+        impedances: &[(DateTime<FixedOffset>, Ohms)],
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        for (measured_at, ohms) in impedances {
+            let utc_offset_seconds = measured_at.offset().local_minus_utc();
+            let ohms: f64 = ohms.into();
+
+            telemetry::time_query(
+                "insert_impedance",
+                sqlx::query_scalar!(
+                    r#"INSERT INTO impedance (user_id, measured_at, utc_offset_seconds, ohms) VALUES ($1, $2::TIMESTAMPTZ, $3, $4) RETURNING impedance_id"#,
+                    user_id,
+                    measured_at,
+                    utc_offset_seconds,
+                    ohms
+                )
+                .fetch_one(&mut *transaction),
+            )
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    async fn find_impedances_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> Result<Vec<Impedance>, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            ohms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let rows = telemetry::time_query(
+            "find_impedances_between_dates",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT impedance_id, user_id, measured_at, utc_offset_seconds, ohms FROM impedance WHERE user_id = $1 AND measured_at BETWEEN $2::TIMESTAMPTZ AND $3::TIMESTAMPTZ ORDER BY measured_at ASC"#,
+                user_id,
+                start_date,
+                end_date
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    ohms: Ohms::new(r.ohms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_weights_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> Result<Vec<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let rows = telemetry::time_query(
+            "find_weights_between_dates",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, utc_offset_seconds, kilograms FROM weight WHERE user_id = $1 AND measured_at BETWEEN $2::TIMESTAMPTZ AND $3::TIMESTAMPTZ ORDER BY measured_at ASC"#,
+                user_id,
+                start_date,
+                end_date
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                })
+            })
+            .collect()
+    }
+
+    fn stream_impedances_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> BoxStream<'static, Result<Impedance, ApiError>> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            ohms: f64,
+        }
+
+        let pool = self.pool.clone();
+        let user_id: i64 = user_id.into();
+        let start_date = *start_date;
+        let end_date = *end_date;
+
+        Box::pin(try_stream! {
+            let start = Instant::now();
+            let mut rows = sqlx::query_as!(
+                Row,
+                r#"SELECT impedance_id, user_id, measured_at, utc_offset_seconds, ohms FROM impedance WHERE user_id = $1 AND measured_at BETWEEN $2::TIMESTAMPTZ AND $3::TIMESTAMPTZ ORDER BY measured_at ASC"#,
+                user_id,
+                start_date,
+                end_date
+            )
+            .fetch(&pool);
+
+            while let Some(r) = rows
+                .try_next()
+                .await
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+            {
+                yield Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    ohms: Ohms::new(r.ohms)?,
+                };
+            }
+
+            telemetry::record_query_duration("stream_impedances_between_dates", start);
+        })
+    }
+
+    fn stream_weights_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> BoxStream<'static, Result<Weight, ApiError>> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            kilograms: f64,
+        }
+
+        let pool = self.pool.clone();
+        let user_id: i64 = user_id.into();
+        let start_date = *start_date;
+        let end_date = *end_date;
+
+        Box::pin(try_stream! {
+            let start = Instant::now();
+            let mut rows = sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, utc_offset_seconds, kilograms FROM weight WHERE user_id = $1 AND measured_at BETWEEN $2::TIMESTAMPTZ AND $3::TIMESTAMPTZ ORDER BY measured_at ASC"#,
+                user_id,
+                start_date,
+                end_date
+            )
+            .fetch(&pool);
+
+            while let Some(r) = rows
+                .try_next()
+                .await
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+            {
+                yield Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                };
+            }
+
+            telemetry::record_query_duration("stream_weights_between_dates", start);
+        })
+    }
+
+    async fn find_impedances_page(
+        &self,
+        user_id: &UserId,
+        page: &PageRequest,
+    ) -> Result<Vec<Impedance>, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            ohms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let cursor_measured_at = page.after.as_ref().map(|c| c.measured_at);
+        let cursor_id = page.after.as_ref().map(|c| c.id);
+        let limit = page.limit as i64;
+
+        let rows = match page.order {
+            SortOrder::Ascending => {
+                telemetry::time_query(
+                    "find_impedances_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT impedance_id, user_id, measured_at, utc_offset_seconds, ohms FROM impedance WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR measured_at >= $2) AND ($3::TIMESTAMPTZ IS NULL OR measured_at <= $3) AND ($4::TIMESTAMPTZ IS NULL OR measured_at > $4 OR (measured_at = $4 AND impedance_id > $5)) ORDER BY measured_at ASC, impedance_id ASC LIMIT $6"#,
+                        user_id,
+                        page.start_date,
+                        page.end_date,
+                        cursor_measured_at,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+            SortOrder::Descending => {
+                telemetry::time_query(
+                    "find_impedances_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT impedance_id, user_id, measured_at, utc_offset_seconds, ohms FROM impedance WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR measured_at >= $2) AND ($3::TIMESTAMPTZ IS NULL OR measured_at <= $3) AND ($4::TIMESTAMPTZ IS NULL OR measured_at < $4 OR (measured_at = $4 AND impedance_id < $5)) ORDER BY measured_at DESC, impedance_id DESC LIMIT $6"#,
+                        user_id,
+                        page.start_date,
+                        page.end_date,
+                        cursor_measured_at,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+        }
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    ohms: Ohms::new(r.ohms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_weights_page(
+        &self,
+        user_id: &UserId,
+        page: &PageRequest,
+    ) -> Result<Vec<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let cursor_measured_at = page.after.as_ref().map(|c| c.measured_at);
+        let cursor_id = page.after.as_ref().map(|c| c.id);
+        let limit = page.limit as i64;
+
+        let rows = match page.order {
+            SortOrder::Ascending => {
+                telemetry::time_query(
+                    "find_weights_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT weight_id, user_id, measured_at, utc_offset_seconds, kilograms FROM weight WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR measured_at >= $2) AND ($3::TIMESTAMPTZ IS NULL OR measured_at <= $3) AND ($4::TIMESTAMPTZ IS NULL OR measured_at > $4 OR (measured_at = $4 AND weight_id > $5)) ORDER BY measured_at ASC, weight_id ASC LIMIT $6"#,
+                        user_id,
+                        page.start_date,
+                        page.end_date,
+                        cursor_measured_at,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+            SortOrder::Descending => {
+                telemetry::time_query(
+                    "find_weights_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT weight_id, user_id, measured_at, utc_offset_seconds, kilograms FROM weight WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR measured_at >= $2) AND ($3::TIMESTAMPTZ IS NULL OR measured_at <= $3) AND ($4::TIMESTAMPTZ IS NULL OR measured_at < $4 OR (measured_at = $4 AND weight_id < $5)) ORDER BY measured_at DESC, weight_id DESC LIMIT $6"#,
+                        user_id,
+                        page.start_date,
+                        page.end_date,
+                        cursor_measured_at,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+        }
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_latest_weight(&self, user_id: &UserId) -> Result<Option<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let row = telemetry::time_query(
+            "find_latest_weight",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, utc_offset_seconds, kilograms FROM weight WHERE user_id = $1::BIGINT ORDER BY measured_at DESC LIMIT 1"#,
+                user_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(|r| {
+            Ok(Weight {
+                weight_id: WeightId::new(r.weight_id),
+                user_id: UserId::new(r.user_id),
+                measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+                kilograms: Kilograms::new(r.kilograms)?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn find_weight_timestamps(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<DateTime<FixedOffset>>, ApiError> {
+        struct Row {
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let rows = telemetry::time_query(
+            "find_weight_timestamps",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT measured_at, utc_offset_seconds FROM weight WHERE user_id = $1 ORDER BY measured_at"#,
+                user_id
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| with_offset(r.measured_at, r.utc_offset_seconds))
+            .collect()
+    }
+
+    async fn delete_weight(&self, weight_id: &WeightId) -> Result<Weight, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            kilograms: f64,
+        }
+
+        let weight_id: i64 = weight_id.into();
+
+        let row = telemetry::time_query(
+            "delete_weight",
+            sqlx::query_as!(
+                Row,
+                r#"DELETE FROM weight WHERE weight_id = $1::BIGINT RETURNING weight_id, user_id, measured_at, utc_offset_seconds, kilograms"#,
+                weight_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        let r = row.ok_or(ApiError::WeightNotFound)?;
+
+        Ok(Weight {
+            weight_id: WeightId::new(r.weight_id),
+            user_id: UserId::new(r.user_id),
+            measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+            kilograms: Kilograms::new(r.kilograms)?,
+        })
+    }
+
+    async fn delete_impedance(&self, impedance_id: &ImpedanceId) -> Result<Impedance, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: DateTime<Utc>,
+            utc_offset_seconds: i32,
+            ohms: f64,
+        }
+
+        let impedance_id: i64 = impedance_id.into();
+
+        let row = telemetry::time_query(
+            "delete_impedance",
+            sqlx::query_as!(
+                Row,
+                r#"DELETE FROM impedance WHERE impedance_id = $1::BIGINT RETURNING impedance_id, user_id, measured_at, utc_offset_seconds, ohms"#,
+                impedance_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        let r = row.ok_or(ApiError::ImpedanceNotFound)?;
+
+        Ok(Impedance {
+            impedance_id: ImpedanceId::new(r.impedance_id),
+            user_id: UserId::new(r.user_id),
+            measured_at: with_offset(r.measured_at, r.utc_offset_seconds)?,
+            ohms: Ohms::new(r.ohms)?,
+        })
+    }
+}
+
+/// Restores the offset the instant was measured with.
+fn with_offset(
+    measured_at: DateTime<Utc>,
+    utc_offset_seconds: i32,
+) -> Result<DateTime<FixedOffset>, ApiError> {
+    let offset = FixedOffset::east_opt(utc_offset_seconds).ok_or_else(|| {
+        ApiError::Unexpected(format!("Invalid UTC offset {}", utc_offset_seconds).into())
+    })?;
+
+    Ok(measured_at.with_timezone(&offset))
+}
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+
+use crate::{error::ApiError, repositories::schema::SchemaRepository, telemetry};
+
+use super::{PostgresRepository, MIGRATOR};
+
+#[async_trait]
+impl SchemaRepository for PostgresRepository {
+    fn migrator(&self) -> &'static Migrator {
+        &MIGRATOR
+    }
+
+    async fn ping(&self) -> Result<(), ApiError> {
+        let _ = telemetry::time_query(
+            "ping",
+            sqlx::query!(r#"SELECT 1::INTEGER AS one"#).fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn run_migrations(&self) -> Result<(), ApiError> {
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    async fn find_applied_migrations(&self) -> Result<Vec<i64>, ApiError> {
+        struct Row {
+            version: i64,
+        }
+
+        // The table is only created by the first migration run
+        let migrations_table = telemetry::time_query(
+            "find_migrations_table",
+            sqlx::query!(r#"SELECT to_regclass('_sqlx_migrations')::TEXT AS name"#)
+                .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if migrations_table.name.is_none() {
+            return Ok(vec![]);
+        }
+
+        let rows = telemetry::time_query(
+            "find_applied_migrations",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT version FROM _sqlx_migrations WHERE success ORDER BY version"#
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(rows.into_iter().map(|r| r.version).collect())
+    }
+
+    async fn snapshot(&self, _path: &Path) -> Result<(), ApiError> {
+        Err(ApiError::SnapshotNotSupported)
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{reminder::ReminderSettings, user::UserId},
+    error::ApiError,
+};
+
+#[async_trait]
+pub trait ReminderRepository: Send + Sync {
+    async fn find_reminder_settings(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Option<ReminderSettings>, ApiError>;
+
+    /// Settings of every user that wants to be reminded.
+    async fn find_all_reminder_settings(&self) -> Result<Vec<ReminderSettings>, ApiError>;
+
+    /// Creates or replaces the settings of the user, when it was last reminded is kept.
+    async fn save_reminder_settings(&self, settings: &ReminderSettings) -> Result<(), ApiError>;
+
+    /// Fails with `RemindersNotFound` when the user had none.
+    async fn delete_reminder_settings(&self, user_id: &UserId) -> Result<(), ApiError>;
+
+    async fn update_last_reminded_at(
+        &self,
+        user_id: &UserId,
+        reminded_at: &DateTime<Utc>,
+    ) -> Result<(), ApiError>;
+}
@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+
+use crate::error::ApiError;
+
+#[async_trait]
+pub trait SchemaRepository: Send + Sync {
+    /// Migrations embedded in the binary for this backend.
+    fn migrator(&self) -> &'static Migrator;
+
+    async fn ping(&self) -> Result<(), ApiError>;
+
+    async fn run_migrations(&self) -> Result<(), ApiError>;
+
+    /// Versions of the migrations successfully applied to the database, in ascending order.
+    async fn find_applied_migrations(&self) -> Result<Vec<i64>, ApiError>;
+
+    /// Versions of the embedded migrations that have not been applied to the database yet.
+    async fn find_pending_migrations(&self) -> Result<Vec<i64>, ApiError> {
+        let applied = self.find_applied_migrations().await?;
+
+        Ok(self
+            .migrator()
+            .iter()
+            .filter(|migration| migration.migration_type.is_up_migration())
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+
+    /// Writes a consistent copy of the database to the path, which must not exist yet.
+    async fn snapshot(&self, path: &Path) -> Result<(), ApiError>;
+}
@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use sqlx::{
+    migrate::{MigrateDatabase, Migrator},
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    Pool, Sqlite,
+};
+
+use crate::configuration::DatabaseConfiguration;
+
+mod digests;
+mod measurements;
+mod reminders;
+mod schema;
+mod users;
+mod webhooks;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+pub struct SqliteRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRepository {
+    /// Opens a connection pool to the database, creating the database file when it does not exist.
+    pub async fn connect(configuration: &DatabaseConfiguration) -> Result<Self, sqlx::Error> {
+        if !Sqlite::database_exists(&configuration.url)
+            .await
+            .unwrap_or(false)
+        {
+            Sqlite::create_database(&configuration.url).await?;
+        }
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(configuration.max_connections)
+            .connect_with(
+                SqliteConnectOptions::from_str(&configuration.url)?
+                    .journal_mode(SqliteJournalMode::Wal),
+            )
+            .await?;
+
+        Ok(SqliteRepository { pool })
+    }
+}
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{
+        digest::{DigestFrequency, DigestSettings},
+        reminder,
+        user::UserId,
+    },
+    error::ApiError,
+    repositories::digests::DigestRepository,
+    telemetry,
+};
+
+use super::SqliteRepository;
+
+struct DigestRow {
+    user_id: i64,
+    email: String,
+    frequency: String,
+    last_sent_at: Option<String>,
+}
+
+impl TryFrom<DigestRow> for DigestSettings {
+    type Error = ApiError;
+
+    fn try_from(r: DigestRow) -> Result<Self, Self::Error> {
+        Ok(DigestSettings {
+            user_id: UserId::new(r.user_id),
+            email: reminder::parse_email(&r.email)?,
+            frequency: DigestFrequency::parse(&r.frequency)?,
+            last_sent_at: r
+                .last_sent_at
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|timestamp| timestamp.to_utc())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[async_trait]
+impl DigestRepository for SqliteRepository {
+    async fn find_digest_settings(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Option<DigestSettings>, ApiError> {
+        let user_id: i64 = user_id.into();
+
+        let row = telemetry::time_query(
+            "find_digest_settings",
+            sqlx::query_as!(
+                DigestRow,
+                r#"SELECT user_id, email, frequency, last_sent_at FROM digest WHERE user_id = $1"#,
+                user_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(DigestSettings::try_from).transpose()
+    }
+
+    async fn find_all_digest_settings(&self) -> Result<Vec<DigestSettings>, ApiError> {
+        let rows = telemetry::time_query(
+            "find_all_digest_settings",
+            sqlx::query_as!(
+                DigestRow,
+                r#"SELECT user_id, email, frequency, last_sent_at FROM digest ORDER BY user_id"#
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter().map(DigestSettings::try_from).collect()
+    }
+
+    async fn save_digest_settings(&self, settings: &DigestSettings) -> Result<(), ApiError> {
+        let user_id: i64 = (&settings.user_id).into();
+        let email: String = settings.email.to_string();
+        let frequency: &str = settings.frequency.name();
+
+        let _ = telemetry::time_query(
+            "save_digest_settings",
+            sqlx::query!(
+                r#"INSERT INTO digest (user_id, email, frequency) VALUES ($1, $2, $3)
+                ON CONFLICT (user_id) DO UPDATE SET email = excluded.email, frequency = excluded.frequency"#,
+                user_id,
+                email,
+                frequency
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn delete_digest_settings(&self, user_id: &UserId) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+
+        let result = telemetry::time_query(
+            "delete_digest_settings",
+            sqlx::query!(r#"DELETE FROM digest WHERE user_id = $1"#, user_id).execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::DigestNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_digest_sent_at(
+        &self,
+        user_id: &UserId,
+        sent_at: &DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let sent_at: String = sent_at.to_rfc3339();
+
+        let _ = telemetry::time_query(
+            "update_digest_sent_at",
+            sqlx::query!(
+                r#"UPDATE digest SET last_sent_at = $1 WHERE user_id = $2"#,
+                sent_at,
+                user_id
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,604 @@
+use std::time::Instant;
+
+use async_stream::try_stream;
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use futures::{stream::BoxStream, TryStreamExt};
+
+use crate::{
+    domain::{
+        impedance::{Impedance, ImpedanceId, Ohms},
+        page::{PageRequest, SortOrder},
+        user::UserId,
+        weight::{Kilograms, Weight, WeightId},
+    },
+    error::ApiError,
+    repositories::measurements::MeasurementRepository,
+    telemetry,
+};
+
+use super::SqliteRepository;
+
+#[async_trait]
+impl MeasurementRepository for SqliteRepository {
+    async fn insert_weight(
+        &self,
+        user_id: &UserId,
+        measured_at: &DateTime<FixedOffset>,
+        kilograms: &Kilograms,
+    ) -> Result<WeightId, ApiError> {
+        let user_id: i64 = user_id.into();
+        let measured_at_epoch_ms = measured_at.timestamp_millis();
+        let kilograms: f64 = kilograms.into();
+
+        let weight_id = telemetry::time_query(
+            "insert_weight",
+            sqlx::query_scalar!(
+                r#"INSERT INTO weight (user_id, measured_at, measured_at_epoch_ms, kilograms) VALUES ($1, $2, $3, $4) RETURNING weight_id AS "weight_id!""#,
+                user_id,
+                measured_at,
+                measured_at_epoch_ms,
+                kilograms
+            )
+            .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(WeightId::new(weight_id))
+    }
+
+    async fn insert_impedance(
+        &self,
+        user_id: &UserId,
+        measured_at: &DateTime<FixedOffset>,
+        ohms: &Ohms,
+    ) -> Result<ImpedanceId, ApiError> {
+        let user_id: i64 = user_id.into();
+        let measured_at_epoch_ms = measured_at.timestamp_millis();
+        let ohms: f64 = ohms.into();
+
+        let impedance_id = telemetry::time_query(
+            "insert_impedance",
+            sqlx::query_scalar!(
+                r#"INSERT INTO impedance (user_id, measured_at, measured_at_epoch_ms, ohms) VALUES ($1, $2, $3, $4) RETURNING impedance_id AS "impedance_id!""#,
+                user_id,
+                measured_at,
+                measured_at_epoch_ms,
+                ohms
+            )
+            .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(ImpedanceId::new(impedance_id))
+    }
+
+    async fn insert_weights(
+        &self,
+        user_id: &UserId,
+        weights: &[(DateTime<FixedOffset>, Kilograms)],
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        for (measured_at, kilograms) in weights {
+            let measured_at_epoch_ms = measured_at.timestamp_millis();
+            let kilograms: f64 = kilograms.into();
+
+            telemetry::time_query(
+                "insert_weight",
+                sqlx::query_scalar!(
+                    r#"INSERT INTO weight (user_id, measured_at, measured_at_epoch_ms, kilograms) VALUES ($1, $2, $3, $4) RETURNING weight_id AS "weight_id!""#,
+                    user_id,
+                    measured_at,
+                    measured_at_epoch_ms,
+                    kilograms
+                )
+                .fetch_one(&mut *transaction),
+            )
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    async fn insert_impedances(
+        &self,
+        user_id: &UserId,
+        impedances: &[(DateTime<FixedOffset>, Ohms)],
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        for (measured_at, ohms) in impedances {
+            let measured_at_epoch_ms = measured_at.timestamp_millis();
+            let ohms: f64 = ohms.into();
+
+            telemetry::time_query(
+                "insert_impedance",
+                sqlx::query_scalar!(
+                    r#"INSERT INTO impedance (user_id, measured_at, measured_at_epoch_ms, ohms) VALUES ($1, $2, $3, $4) RETURNING impedance_id AS "impedance_id!""#,
+                    user_id,
+                    measured_at,
+                    measured_at_epoch_ms,
+                    ohms
+                )
+                .fetch_one(&mut *transaction),
+            )
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))
+    }
+
+    async fn find_impedances_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> Result<Vec<Impedance>, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: String,
+            ohms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = start_date.timestamp_millis();
+        let end_epoch_ms = end_date.timestamp_millis();
+
+        let rows = telemetry::time_query(
+            "find_impedances_between_dates",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT impedance_id, user_id, measured_at, ohms FROM impedance WHERE user_id = $1 AND measured_at_epoch_ms BETWEEN $2 AND $3 ORDER BY measured_at_epoch_ms ASC"#,
+                user_id,
+                start_epoch_ms,
+                end_epoch_ms
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    ohms: Ohms::new(r.ohms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_weights_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> Result<Vec<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: String,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = start_date.timestamp_millis();
+        let end_epoch_ms = end_date.timestamp_millis();
+
+        let rows = telemetry::time_query(
+            "find_weights_between_dates",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, kilograms FROM weight WHERE user_id = $1 AND measured_at_epoch_ms BETWEEN $2 AND $3 ORDER BY measured_at_epoch_ms ASC"#,
+                user_id,
+                start_epoch_ms,
+                end_epoch_ms
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                })
+            })
+            .collect()
+    }
+
+    fn stream_impedances_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> BoxStream<'static, Result<Impedance, ApiError>> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: String,
+            ohms: f64,
+        }
+
+        let pool = self.pool.clone();
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = start_date.timestamp_millis();
+        let end_epoch_ms = end_date.timestamp_millis();
+
+        Box::pin(try_stream! {
+            let start = Instant::now();
+            let mut rows = sqlx::query_as!(
+                Row,
+                r#"SELECT impedance_id, user_id, measured_at, ohms FROM impedance WHERE user_id = $1 AND measured_at_epoch_ms BETWEEN $2 AND $3 ORDER BY measured_at_epoch_ms ASC"#,
+                user_id,
+                start_epoch_ms,
+                end_epoch_ms
+            )
+            .fetch(&pool);
+
+            while let Some(r) = rows
+                .try_next()
+                .await
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+            {
+                yield Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    ohms: Ohms::new(r.ohms)?,
+                };
+            }
+
+            telemetry::record_query_duration("stream_impedances_between_dates", start);
+        })
+    }
+
+    fn stream_weights_between_dates(
+        &self,
+        user_id: &UserId,
+        start_date: &DateTime<FixedOffset>,
+        end_date: &DateTime<FixedOffset>,
+    ) -> BoxStream<'static, Result<Weight, ApiError>> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: String,
+            kilograms: f64,
+        }
+
+        let pool = self.pool.clone();
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = start_date.timestamp_millis();
+        let end_epoch_ms = end_date.timestamp_millis();
+
+        Box::pin(try_stream! {
+            let start = Instant::now();
+            let mut rows = sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, kilograms FROM weight WHERE user_id = $1 AND measured_at_epoch_ms BETWEEN $2 AND $3 ORDER BY measured_at_epoch_ms ASC"#,
+                user_id,
+                start_epoch_ms,
+                end_epoch_ms
+            )
+            .fetch(&pool);
+
+            while let Some(r) = rows
+                .try_next()
+                .await
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+            {
+                yield Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                };
+            }
+
+            telemetry::record_query_duration("stream_weights_between_dates", start);
+        })
+    }
+
+    async fn find_impedances_page(
+        &self,
+        user_id: &UserId,
+        page: &PageRequest,
+    ) -> Result<Vec<Impedance>, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: String,
+            ohms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = page.start_date.map(|date| date.timestamp_millis());
+        let end_epoch_ms = page.end_date.map(|date| date.timestamp_millis());
+        let cursor_epoch_ms = page
+            .after
+            .as_ref()
+            .map(|c| c.measured_at.timestamp_millis());
+        let cursor_id = page.after.as_ref().map(|c| c.id);
+        let limit = page.limit as i64;
+
+        let rows = match page.order {
+            SortOrder::Ascending => {
+                telemetry::time_query(
+                    "find_impedances_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT impedance_id, user_id, measured_at, ohms FROM impedance WHERE user_id = $1 AND ($2 IS NULL OR measured_at_epoch_ms >= $2) AND ($3 IS NULL OR measured_at_epoch_ms <= $3) AND ($4 IS NULL OR measured_at_epoch_ms > $4 OR (measured_at_epoch_ms = $4 AND impedance_id > $5)) ORDER BY measured_at_epoch_ms ASC, impedance_id ASC LIMIT $6"#,
+                        user_id,
+                        start_epoch_ms,
+                        end_epoch_ms,
+                        cursor_epoch_ms,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+            SortOrder::Descending => {
+                telemetry::time_query(
+                    "find_impedances_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT impedance_id, user_id, measured_at, ohms FROM impedance WHERE user_id = $1 AND ($2 IS NULL OR measured_at_epoch_ms >= $2) AND ($3 IS NULL OR measured_at_epoch_ms <= $3) AND ($4 IS NULL OR measured_at_epoch_ms < $4 OR (measured_at_epoch_ms = $4 AND impedance_id < $5)) ORDER BY measured_at_epoch_ms DESC, impedance_id DESC LIMIT $6"#,
+                        user_id,
+                        start_epoch_ms,
+                        end_epoch_ms,
+                        cursor_epoch_ms,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+        }
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Impedance {
+                    impedance_id: ImpedanceId::new(r.impedance_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    ohms: Ohms::new(r.ohms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_weights_page(
+        &self,
+        user_id: &UserId,
+        page: &PageRequest,
+    ) -> Result<Vec<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: String,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+        let start_epoch_ms = page.start_date.map(|date| date.timestamp_millis());
+        let end_epoch_ms = page.end_date.map(|date| date.timestamp_millis());
+        let cursor_epoch_ms = page
+            .after
+            .as_ref()
+            .map(|c| c.measured_at.timestamp_millis());
+        let cursor_id = page.after.as_ref().map(|c| c.id);
+        let limit = page.limit as i64;
+
+        let rows = match page.order {
+            SortOrder::Ascending => {
+                telemetry::time_query(
+                    "find_weights_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT weight_id, user_id, measured_at, kilograms FROM weight WHERE user_id = $1 AND ($2 IS NULL OR measured_at_epoch_ms >= $2) AND ($3 IS NULL OR measured_at_epoch_ms <= $3) AND ($4 IS NULL OR measured_at_epoch_ms > $4 OR (measured_at_epoch_ms = $4 AND weight_id > $5)) ORDER BY measured_at_epoch_ms ASC, weight_id ASC LIMIT $6"#,
+                        user_id,
+                        start_epoch_ms,
+                        end_epoch_ms,
+                        cursor_epoch_ms,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+            SortOrder::Descending => {
+                telemetry::time_query(
+                    "find_weights_page",
+                    sqlx::query_as!(
+                        Row,
+                        r#"SELECT weight_id, user_id, measured_at, kilograms FROM weight WHERE user_id = $1 AND ($2 IS NULL OR measured_at_epoch_ms >= $2) AND ($3 IS NULL OR measured_at_epoch_ms <= $3) AND ($4 IS NULL OR measured_at_epoch_ms < $4 OR (measured_at_epoch_ms = $4 AND weight_id < $5)) ORDER BY measured_at_epoch_ms DESC, weight_id DESC LIMIT $6"#,
+                        user_id,
+                        start_epoch_ms,
+                        end_epoch_ms,
+                        cursor_epoch_ms,
+                        cursor_id,
+                        limit
+                    )
+                    .fetch_all(&self.pool),
+                )
+                .await
+            }
+        }
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(Weight {
+                    weight_id: WeightId::new(r.weight_id),
+                    user_id: UserId::new(r.user_id),
+                    measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                    kilograms: Kilograms::new(r.kilograms)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_latest_weight(&self, user_id: &UserId) -> Result<Option<Weight>, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: String,
+            kilograms: f64,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let row = telemetry::time_query(
+            "find_latest_weight",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT weight_id, user_id, measured_at, kilograms FROM weight WHERE user_id = $1 ORDER BY measured_at_epoch_ms DESC LIMIT 1"#,
+                user_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(|r| {
+            Ok(Weight {
+                weight_id: WeightId::new(r.weight_id),
+                user_id: UserId::new(r.user_id),
+                measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+                kilograms: Kilograms::new(r.kilograms)?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn find_weight_timestamps(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Vec<DateTime<FixedOffset>>, ApiError> {
+        let user_id: i64 = user_id.into();
+
+        let rows = telemetry::time_query(
+            "find_weight_timestamps",
+            sqlx::query!(
+                r#"SELECT measured_at FROM weight WHERE user_id = $1 ORDER BY measured_at_epoch_ms"#,
+                user_id
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                    .map_err(|e| ApiError::Unexpected(Box::new(e)))
+            })
+            .collect()
+    }
+
+    async fn delete_weight(&self, weight_id: &WeightId) -> Result<Weight, ApiError> {
+        struct Row {
+            weight_id: i64,
+            user_id: i64,
+            measured_at: String,
+            kilograms: f64,
+        }
+
+        let weight_id: i64 = weight_id.into();
+
+        let row = telemetry::time_query(
+            "delete_weight",
+            sqlx::query_as!(
+                Row,
+                r#"DELETE FROM weight WHERE weight_id = $1 RETURNING weight_id AS "weight_id!", user_id AS "user_id!", measured_at AS "measured_at!", kilograms AS "kilograms!""#,
+                weight_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        let r = row.ok_or(ApiError::WeightNotFound)?;
+
+        Ok(Weight {
+            weight_id: WeightId::new(r.weight_id),
+            user_id: UserId::new(r.user_id),
+            measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+            kilograms: Kilograms::new(r.kilograms)?,
+        })
+    }
+
+    async fn delete_impedance(&self, impedance_id: &ImpedanceId) -> Result<Impedance, ApiError> {
+        struct Row {
+            impedance_id: i64,
+            user_id: i64,
+            measured_at: String,
+            ohms: f64,
+        }
+
+        let impedance_id: i64 = impedance_id.into();
+
+        let row = telemetry::time_query(
+            "delete_impedance",
+            sqlx::query_as!(
+                Row,
+                r#"DELETE FROM impedance WHERE impedance_id = $1 RETURNING impedance_id AS "impedance_id!", user_id AS "user_id!", measured_at AS "measured_at!", ohms AS "ohms!""#,
+                impedance_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        let r = row.ok_or(ApiError::ImpedanceNotFound)?;
+
+        Ok(Impedance {
+            impedance_id: ImpedanceId::new(r.impedance_id),
+            user_id: UserId::new(r.user_id),
+            measured_at: DateTime::parse_from_rfc3339(r.measured_at.as_str())
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+            ohms: Ohms::new(r.ohms)?,
+        })
+    }
+}
@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{
+        reminder::{self, QuietHours, ReminderSettings},
+        user::UserId,
+    },
+    error::ApiError,
+    repositories::reminders::ReminderRepository,
+    telemetry,
+};
+
+use super::SqliteRepository;
+
+struct ReminderRow {
+    user_id: i64,
+    after_days: Option<i64>,
+    email: Option<String>,
+    ntfy_topic: Option<String>,
+    quiet_hours_start: Option<String>,
+    quiet_hours_end: Option<String>,
+    last_reminded_at: Option<String>,
+}
+
+impl TryFrom<ReminderRow> for ReminderSettings {
+    type Error = ApiError;
+
+    fn try_from(r: ReminderRow) -> Result<Self, Self::Error> {
+        let quiet_hours = match (r.quiet_hours_start, r.quiet_hours_end) {
+            (Some(start), Some(end)) => Some(QuietHours {
+                start: reminder::parse_time(&start)?,
+                end: reminder::parse_time(&end)?,
+            }),
+            _ => None,
+        };
+
+        Ok(ReminderSettings {
+            user_id: UserId::new(r.user_id),
+            after_days: r
+                .after_days
+                .map(u32::try_from)
+                .transpose()
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+            email: r.email.as_deref().map(reminder::parse_email).transpose()?,
+            ntfy_topic: r.ntfy_topic,
+            quiet_hours,
+            last_reminded_at: r
+                .last_reminded_at
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|timestamp| timestamp.to_utc())
+                        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[async_trait]
+impl ReminderRepository for SqliteRepository {
+    async fn find_reminder_settings(
+        &self,
+        user_id: &UserId,
+    ) -> Result<Option<ReminderSettings>, ApiError> {
+        let user_id: i64 = user_id.into();
+
+        let row = telemetry::time_query(
+            "find_reminder_settings",
+            sqlx::query_as!(
+                ReminderRow,
+                r#"SELECT user_id, after_days, email, ntfy_topic, quiet_hours_start, quiet_hours_end, last_reminded_at FROM reminder WHERE user_id = $1"#,
+                user_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(ReminderSettings::try_from).transpose()
+    }
+
+    async fn find_all_reminder_settings(&self) -> Result<Vec<ReminderSettings>, ApiError> {
+        let rows = telemetry::time_query(
+            "find_all_reminder_settings",
+            sqlx::query_as!(
+                ReminderRow,
+                r#"SELECT user_id, after_days, email, ntfy_topic, quiet_hours_start, quiet_hours_end, last_reminded_at FROM reminder ORDER BY user_id"#
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter().map(ReminderSettings::try_from).collect()
+    }
+
+    async fn save_reminder_settings(&self, settings: &ReminderSettings) -> Result<(), ApiError> {
+        let user_id: i64 = (&settings.user_id).into();
+        let after_days: Option<i64> = settings.after_days.map(i64::from);
+        let email: Option<String> = settings.email.as_ref().map(ToString::to_string);
+        let quiet_hours_start: Option<String> = settings
+            .quiet_hours
+            .as_ref()
+            .map(|quiet_hours| reminder::format_time(&quiet_hours.start));
+        let quiet_hours_end: Option<String> = settings
+            .quiet_hours
+            .as_ref()
+            .map(|quiet_hours| reminder::format_time(&quiet_hours.end));
+
+        let _ = telemetry::time_query(
+            "save_reminder_settings",
+            sqlx::query!(
+                r#"INSERT INTO reminder (user_id, after_days, email, ntfy_topic, quiet_hours_start, quiet_hours_end) VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_id) DO UPDATE SET after_days = excluded.after_days, email = excluded.email, ntfy_topic = excluded.ntfy_topic, quiet_hours_start = excluded.quiet_hours_start, quiet_hours_end = excluded.quiet_hours_end"#,
+                user_id,
+                after_days,
+                email,
+                settings.ntfy_topic,
+                quiet_hours_start,
+                quiet_hours_end
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn delete_reminder_settings(&self, user_id: &UserId) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+
+        let result = telemetry::time_query(
+            "delete_reminder_settings",
+            sqlx::query!(r#"DELETE FROM reminder WHERE user_id = $1"#, user_id).execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::RemindersNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_last_reminded_at(
+        &self,
+        user_id: &UserId,
+        reminded_at: &DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let user_id: i64 = user_id.into();
+        let reminded_at: String = reminded_at.to_rfc3339();
+
+        let _ = telemetry::time_query(
+            "update_last_reminded_at",
+            sqlx::query!(
+                r#"UPDATE reminder SET last_reminded_at = $1 WHERE user_id = $2"#,
+                reminded_at,
+                user_id
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use chrono_tz::Tz;
+
+use crate::{
+    domain::{
+        user::{self, Centimeters, User, UserId, UserName},
+        weight::Kilograms,
+    },
+    error::ApiError,
+    repositories::users::UserRepository,
+    telemetry,
+};
+
+use super::SqliteRepository;
+
+#[async_trait]
+impl UserRepository for SqliteRepository {
+    async fn insert_user(
+        &self,
+        name: &UserName,
+        height: Option<&Centimeters>,
+        timezone: Option<&Tz>,
+        goal: Option<&Kilograms>,
+    ) -> Result<(), ApiError> {
+        let name: String = name.into();
+        let height_centimeters: Option<f64> = height.map(f64::from);
+        let timezone: Option<&str> = timezone.map(|timezone| timezone.name());
+        let goal_kilograms: Option<f64> = goal.map(f64::from);
+
+        let _ = telemetry::time_query(
+            "insert_user",
+            sqlx::query!(
+                r#"INSERT INTO users (name, height_centimeters, timezone, goal_kilograms) VALUES ($1, $2, $3, $4)"#,
+                name,
+                height_centimeters,
+                timezone,
+                goal_kilograms
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn find_users(&self) -> Result<Vec<User>, ApiError> {
+        struct Row {
+            id: i64,
+            name: String,
+            height_centimeters: Option<f64>,
+            timezone: Option<String>,
+            goal_kilograms: Option<f64>,
+        }
+
+        let rows = telemetry::time_query(
+            "find_users",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT id, name, height_centimeters, timezone, goal_kilograms FROM users"#
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(User {
+                    id: UserId::new(r.id),
+                    name: UserName::new(r.name),
+                    height: r.height_centimeters.map(Centimeters::new).transpose()?,
+                    timezone: r
+                        .timezone
+                        .as_deref()
+                        .map(user::parse_timezone)
+                        .transpose()?,
+                    goal: r.goal_kilograms.map(Kilograms::new).transpose()?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_user(&self, user_id: &UserId) -> Result<Option<User>, ApiError> {
+        struct Row {
+            id: i64,
+            name: String,
+            height_centimeters: Option<f64>,
+            timezone: Option<String>,
+            goal_kilograms: Option<f64>,
+        }
+
+        let user_id: i64 = user_id.into();
+
+        let row = telemetry::time_query(
+            "find_user",
+            sqlx::query_as!(
+                Row,
+                r#"SELECT id, name, height_centimeters, timezone, goal_kilograms FROM users where id = $1"#,
+                user_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(|r| {
+            Ok(User {
+                id: UserId::new(r.id),
+                name: UserName::new(r.name),
+                height: r.height_centimeters.map(Centimeters::new).transpose()?,
+                timezone: r
+                    .timezone
+                    .as_deref()
+                    .map(user::parse_timezone)
+                    .transpose()?,
+                goal: r.goal_kilograms.map(Kilograms::new).transpose()?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn update_user(
+        &self,
+        id: &UserId,
+        name: &UserName,
+        height: Option<&Centimeters>,
+        timezone: Option<&Tz>,
+        goal: Option<&Kilograms>,
+    ) -> Result<(), ApiError> {
+        let id: i64 = id.into();
+        let name: String = name.into();
+        let height_centimeters: Option<f64> = height.map(f64::from);
+        let timezone: Option<&str> = timezone.map(|timezone| timezone.name());
+        let goal_kilograms: Option<f64> = goal.map(f64::from);
+
+        let result = telemetry::time_query(
+            "update_user",
+            sqlx::query!(
+                r#"UPDATE users SET name = $1, height_centimeters = $2, timezone = $3, goal_kilograms = $4 WHERE id = $5"#,
+                name,
+                height_centimeters,
+                timezone,
+                goal_kilograms,
+                id
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, id: &UserId) -> Result<(), ApiError> {
+        let id: i64 = id.into();
+
+        let result = telemetry::time_query(
+            "delete_user",
+            sqlx::query!(r#"DELETE FROM users WHERE id = $1"#, id).execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::UserNotFound);
+        }
+
+        Ok(())
+    }
+}
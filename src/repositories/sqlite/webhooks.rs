@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{
+        user::UserId,
+        webhook::{self, Delivery, Webhook, WebhookEvent, WebhookId, WebhookUrl},
+    },
+    error::ApiError,
+    repositories::webhooks::WebhookRepository,
+    telemetry,
+};
+
+use super::SqliteRepository;
+
+struct WebhookRow {
+    webhook_id: i64,
+    user_id: Option<i64>,
+    url: String,
+    secret: String,
+    events: String,
+    created_at: String,
+}
+
+impl TryFrom<WebhookRow> for Webhook {
+    type Error = ApiError;
+
+    fn try_from(r: WebhookRow) -> Result<Self, Self::Error> {
+        Ok(Webhook {
+            webhook_id: WebhookId::new(r.webhook_id),
+            user_id: r.user_id.map(UserId::new),
+            url: WebhookUrl::new(&r.url)?,
+            secret: r.secret,
+            events: webhook::parse_events(&r.events)?,
+            created_at: parse_timestamp(&r.created_at)?,
+        })
+    }
+}
+
+struct DeliveryRow {
+    webhook_id: i64,
+    event: String,
+    payload: String,
+    attempt: i64,
+    attempted_at: String,
+    status_code: Option<i64>,
+    error: Option<String>,
+    succeeded: bool,
+}
+
+impl TryFrom<DeliveryRow> for Delivery {
+    type Error = ApiError;
+
+    fn try_from(r: DeliveryRow) -> Result<Self, Self::Error> {
+        Ok(Delivery {
+            webhook_id: WebhookId::new(r.webhook_id),
+            event: r.event,
+            payload: r.payload,
+            attempt: u32::try_from(r.attempt).map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+            attempted_at: parse_timestamp(&r.attempted_at)?,
+            status_code: r
+                .status_code
+                .map(u16::try_from)
+                .transpose()
+                .map_err(|e| ApiError::Unexpected(Box::new(e)))?,
+            error: r.error,
+            succeeded: r.succeeded,
+        })
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqliteRepository {
+    async fn insert_webhook(
+        &self,
+        user_id: Option<&UserId>,
+        url: &WebhookUrl,
+        secret: &str,
+        events: &[WebhookEvent],
+        created_at: &DateTime<Utc>,
+    ) -> Result<WebhookId, ApiError> {
+        let user_id: Option<i64> = user_id.map(i64::from);
+        let url: String = url.into();
+        let events: String = webhook::format_events(events);
+        let created_at: String = created_at.to_rfc3339();
+
+        let webhook_id = telemetry::time_query(
+            "insert_webhook",
+            sqlx::query_scalar!(
+                r#"INSERT INTO webhook (user_id, url, secret, events, created_at) VALUES ($1, $2, $3, $4, $5) RETURNING webhook_id AS "webhook_id!""#,
+                user_id,
+                url,
+                secret,
+                events,
+                created_at
+            )
+            .fetch_one(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(WebhookId::new(webhook_id))
+    }
+
+    async fn find_webhooks(&self) -> Result<Vec<Webhook>, ApiError> {
+        let rows = telemetry::time_query(
+            "find_webhooks",
+            sqlx::query_as!(
+                WebhookRow,
+                r#"SELECT webhook_id, user_id, url, secret, events, created_at FROM webhook ORDER BY webhook_id"#
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter().map(Webhook::try_from).collect()
+    }
+
+    async fn find_webhook(&self, webhook_id: &WebhookId) -> Result<Option<Webhook>, ApiError> {
+        let webhook_id: i64 = webhook_id.into();
+
+        let row = telemetry::time_query(
+            "find_webhook",
+            sqlx::query_as!(
+                WebhookRow,
+                r#"SELECT webhook_id, user_id, url, secret, events, created_at FROM webhook WHERE webhook_id = $1"#,
+                webhook_id
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        row.map(Webhook::try_from).transpose()
+    }
+
+    async fn delete_webhook(&self, webhook_id: &WebhookId) -> Result<(), ApiError> {
+        let webhook_id: i64 = webhook_id.into();
+
+        let result = telemetry::time_query(
+            "delete_webhook",
+            sqlx::query!(r#"DELETE FROM webhook WHERE webhook_id = $1"#, webhook_id)
+                .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::WebhookNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn insert_delivery(&self, delivery: &Delivery) -> Result<(), ApiError> {
+        let webhook_id: i64 = (&delivery.webhook_id).into();
+        let attempt: i64 = delivery.attempt.into();
+        let attempted_at: String = delivery.attempted_at.to_rfc3339();
+        let status_code: Option<i64> = delivery.status_code.map(i64::from);
+
+        let _ = telemetry::time_query(
+            "insert_delivery",
+            sqlx::query!(
+                r#"INSERT INTO webhook_delivery (webhook_id, event, payload, attempt, attempted_at, status_code, error, succeeded) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                webhook_id,
+                delivery.event,
+                delivery.payload,
+                attempt,
+                attempted_at,
+                status_code,
+                delivery.error,
+                delivery.succeeded
+            )
+            .execute(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn find_deliveries(
+        &self,
+        webhook_id: &WebhookId,
+        limit: u32,
+    ) -> Result<Vec<Delivery>, ApiError> {
+        let webhook_id: i64 = webhook_id.into();
+
+        let rows = telemetry::time_query(
+            "find_deliveries",
+            sqlx::query_as!(
+                DeliveryRow,
+                r#"SELECT webhook_id, event, payload, attempt, attempted_at, status_code, error, succeeded FROM webhook_delivery WHERE webhook_id = $1 ORDER BY webhook_delivery_id DESC LIMIT $2"#,
+                webhook_id,
+                limit
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter().map(Delivery::try_from).collect()
+    }
+
+    async fn find_pending_deliveries(&self, max_attempts: u32) -> Result<Vec<Delivery>, ApiError> {
+        let rows = telemetry::time_query(
+            "find_pending_deliveries",
+            sqlx::query_as!(
+                DeliveryRow,
+                r#"SELECT webhook_id, event, payload, attempt, attempted_at, status_code, error, succeeded FROM webhook_delivery AS delivery WHERE NOT succeeded AND attempt < $1 AND NOT EXISTS (SELECT 1 FROM webhook_delivery AS later WHERE later.webhook_id = delivery.webhook_id AND later.payload = delivery.payload AND later.attempt > delivery.attempt) ORDER BY webhook_delivery_id"#,
+                max_attempts
+            )
+            .fetch_all(&self.pool),
+        )
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        rows.into_iter().map(Delivery::try_from).collect()
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, ApiError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.to_utc())
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{
+        user::UserId,
+        webhook::{Delivery, Webhook, WebhookEvent, WebhookId, WebhookUrl},
+    },
+    error::ApiError,
+};
+
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    /// Id of the webhook inserted, it gets the events of every user when there is no user.
+    async fn insert_webhook(
+        &self,
+        user_id: Option<&UserId>,
+        url: &WebhookUrl,
+        secret: &str,
+        events: &[WebhookEvent],
+        created_at: &DateTime<Utc>,
+    ) -> Result<WebhookId, ApiError>;
+
+    /// Every webhook, oldest first.
+    async fn find_webhooks(&self) -> Result<Vec<Webhook>, ApiError>;
+
+    async fn find_webhook(&self, webhook_id: &WebhookId) -> Result<Option<Webhook>, ApiError>;
+
+    /// Deletes the webhook together with its deliveries, fails with `WebhookNotFound` when there
+    /// was none.
+    async fn delete_webhook(&self, webhook_id: &WebhookId) -> Result<(), ApiError>;
+
+    async fn insert_delivery(&self, delivery: &Delivery) -> Result<(), ApiError>;
+
+    /// At most `limit` deliveries to the webhook, newest first.
+    async fn find_deliveries(
+        &self,
+        webhook_id: &WebhookId,
+        limit: u32,
+    ) -> Result<Vec<Delivery>, ApiError>;
+
+    /// Latest attempt of every delivery whose attempts all failed without reaching `max_attempts`,
+    /// oldest first.
+    async fn find_pending_deliveries(&self, max_attempts: u32) -> Result<Vec<Delivery>, ApiError>;
+}
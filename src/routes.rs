@@ -1,11 +1,17 @@
 mod api;
+mod assets;
 mod chart;
+mod health;
 mod index;
+mod metrics;
 mod not_found;
 mod table;
 
-pub use api::api;
+pub use api::{api, api_v1, docs};
+pub use assets::assets;
 pub use chart::chart;
+pub use health::health;
 pub use index::index;
+pub use metrics::metrics;
 pub use not_found::not_found;
 pub use table::table;
@@ -1,5 +1,17 @@
+mod admin;
+mod charts;
+mod dates;
+mod digests;
+mod docs;
+mod events;
 mod measurements;
+mod negotiation;
+mod problem;
+mod reminders;
+mod reports;
 mod routes;
 mod users;
+mod webhooks;
 
-pub use routes::api;
+pub use docs::docs;
+pub use routes::{api, api_v1};
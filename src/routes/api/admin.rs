@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Query, Request, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::{HeaderMap, Response, StatusCode};
+use axum::middleware::{self, Next};
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use subtle::ConstantTimeEq;
+use tokio_util::io::ReaderStream;
+use utoipa::OpenApi;
+
+use crate::app_state::AppState;
+use crate::backup;
+use crate::domain::user::{User, UserId};
+use crate::domain::weight::Weight;
+use crate::error::ApiError;
+use crate::export::{self, Exportable, UserMeasurement};
+
+use super::dates;
+use super::measurements::{attachment, streamed};
+use super::negotiation::{self, Format};
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+pub fn admin(state: AppState) -> Router {
+    Router::new()
+        .route("/admin/backup", get(get_backup))
+        .route("/admin/export", get(get_export))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_token,
+        ))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_backup, get_export), components(schemas(UserMeasurement)))]
+pub struct AdminApi;
+
+pub(super) async fn require_admin_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<axum::response::Response, ApiError> {
+    // The endpoints hand out the data of every user, they are never open
+    let Some(token) = &state.admin_token else {
+        return Err(ApiError::AdminDisabled);
+    };
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(token.as_bytes())));
+
+    if !authorized {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Consistent copy of the SQLite database.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/backup",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = OK, content(("application/vnd.sqlite3"))),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_IMPLEMENTED, description = "The database is not SQLite", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_backup(State(state): State<AppState>) -> Result<Response<Body>, ApiError> {
+    // A directory of its own, that only this user can read and whose name nobody can guess
+    let directory = tempfile::Builder::new()
+        .prefix("weight-tracker-backup-")
+        .tempdir()
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+    let path = backup::snapshot(&*state.repository, directory.path()).await?;
+
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    // The opened file can still be read after removing it, so nothing is left behind
+    directory
+        .close()
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/vnd.sqlite3")
+        .header(
+            CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"weight-tracker-{}.db\"",
+                Utc::now().format("%Y%m%dT%H%M%SZ")
+            ),
+        )
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+const EXPORT_FORMATS: &[Format] = &[Format::Json, Format::Csv, Format::Xlsx];
+
+/// Measurements of every user in a single file, optionally only of one kind and between dates.
+///
+/// The format follows the `Accept` header or the `format` parameter, which takes precedence.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/export",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, the Unix epoch by default"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, now by default"),
+        ("measurement" = Option<String>, Query, description = "`weights` or `impedances`, both by default"),
+        ("format" = Option<String>, Query, description = "`json`, `csv` or `xlsx`, overrides the Accept header"),
+    ),
+    responses(
+        (status = OK, description = "Weights first, then impedances, each by user and oldest first",
+            content(
+                (Vec<UserMeasurement> = "application/json"),
+                ("text/csv"),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            )
+        ),
+        (status = BAD_REQUEST, description = "Invalid dates or measurement", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_ACCEPTABLE, description = "None of the accepted formats can be produced", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_export(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, ApiError> {
+    let format = negotiation::negotiate(&headers, &params, EXPORT_FORMATS)?;
+
+    let (start_date, end_date) =
+        dates::range(&params, |_| DateTime::<Utc>::UNIX_EPOCH.fixed_offset())?;
+
+    let (weights, impedances) = match params.get("measurement").map(String::as_str) {
+        None => (true, true),
+        Some("weights") => (true, false),
+        Some("impedances") => (false, true),
+        Some(_) => return Err(ApiError::InvalidMeasurement),
+    };
+
+    let users = state.repository.find_users().await?;
+    let repository = &state.repository;
+
+    let mut streams = Vec::new();
+    if weights {
+        streams.extend(user_measurements(&users, |user_id| {
+            repository.stream_weights_between_dates(user_id, &start_date, &end_date)
+        }));
+    }
+    if impedances {
+        streams.extend(user_measurements(&users, |user_id| {
+            repository.stream_impedances_between_dates(user_id, &start_date, &end_date)
+        }));
+    }
+    let measurements = stream::iter(streams).flatten().boxed();
+
+    let body = match format {
+        Format::Csv => streamed(export::csv_stream(measurements)),
+        Format::Xlsx => {
+            let (weight_rows, impedance_rows): (Vec<_>, Vec<_>) = measurements
+                .try_collect::<Vec<_>>()
+                .await?
+                .into_iter()
+                .partition(|measurement| measurement.measurement == Weight::KIND);
+
+            export::users_xlsx(
+                weights.then_some(&weight_rows),
+                impedances.then_some(&impedance_rows),
+            )?
+            .into()
+        }
+        _ => streamed(export::json_stream(measurements)),
+    };
+
+    attachment(
+        Response::builder().status(StatusCode::OK),
+        format,
+        "measurements",
+    )
+    .body(body)
+    .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+/// A stream for each user of the measurements read with the function, by user id.
+fn user_measurements<T: Exportable>(
+    users: &[User],
+    measurements: impl Fn(&UserId) -> BoxStream<'static, Result<T, ApiError>>,
+) -> Vec<BoxStream<'static, Result<UserMeasurement, ApiError>>> {
+    users
+        .iter()
+        .map(|user| {
+            let user_id: i64 = (&user.id).into();
+            let user_name: Arc<str> = String::from(&user.name).into();
+
+            measurements(&user.id)
+                .map_ok(move |measurement| {
+                    UserMeasurement::new(user_id, user_name.clone(), &measurement)
+                })
+                .boxed()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::header::AUTHORIZATION;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_backups_both_succeed() {
+        // An in-memory database would be vacuumed into memory as well
+        let directory = tempfile::tempdir().unwrap();
+        let database = format!("sqlite://{}", directory.path().join("test.db").display());
+        let state = AppState::for_tests(&database, Some("secret")).await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/admin/backup", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, admin(state)).await });
+
+        let client = reqwest::Client::new();
+        let download = || async {
+            let response = client
+                .get(&url)
+                .header(AUTHORIZATION, "Bearer secret")
+                .send()
+                .await
+                .unwrap();
+            (response.status(), response.bytes().await.unwrap())
+        };
+
+        let ((first_status, first), (second_status, second)) = tokio::join!(download(), download());
+
+        assert_eq!(first_status, StatusCode::OK);
+        assert_eq!(second_status, StatusCode::OK);
+        assert!(first.starts_with(b"SQLite format 3\0"));
+        assert!(second.starts_with(b"SQLite format 3\0"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::Duration;
+use utoipa::OpenApi;
+
+use crate::app_state::AppState;
+use crate::charts::WeightChart;
+use crate::domain::user::UserId;
+use crate::error::ApiError;
+
+use super::dates;
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 400;
+const SIZES: std::ops::RangeInclusive<u32> = 50..=4000;
+/// Days shown up to the end date when there is no start date.
+const DEFAULT_DAYS: i64 = 30;
+
+pub fn charts(state: AppState) -> Router {
+    Router::new()
+        .route("/users/{id}/chart.svg", get(get_chart_svg))
+        .route("/users/{id}/chart.png", get(get_chart_png))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_chart_svg, get_chart_png))]
+pub struct ChartsApi;
+
+/// Renders the chart of the weights of the user between the dates of the query parameters, on a
+/// blocking thread since rasterizing large charts keeps the CPU busy for a while.
+async fn chart(
+    state: &AppState,
+    id: i64,
+    params: &HashMap<String, String>,
+    render: impl FnOnce(&WeightChart) -> Result<Vec<u8>, ApiError> + Send + 'static,
+) -> Result<Vec<u8>, ApiError> {
+    let size = |name: &'static str, default: u32| {
+        params
+            .get(name)
+            .map(|size| {
+                size.parse::<u32>()
+                    .ok()
+                    .filter(|size| SIZES.contains(size))
+                    .ok_or(ApiError::InvalidChartSize(name))
+            })
+            .transpose()
+            .map(|size| size.unwrap_or(default))
+    };
+
+    let (start_date, end_date) = dates::range(params, |end| end - Duration::days(DEFAULT_DAYS))?;
+    let width = size("width", DEFAULT_WIDTH)?;
+    let height = size("height", DEFAULT_HEIGHT)?;
+
+    let user = state
+        .repository
+        .find_user(&UserId::new(id))
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    let weights = state
+        .repository
+        .find_weights_between_dates(&user.id, &start_date, &end_date)
+        .await?;
+
+    let goal = user.goal.as_ref().map(f64::from);
+    let timezone = user.timezone_or(state.timezone);
+
+    tokio::task::spawn_blocking(move || {
+        render(&WeightChart {
+            weights: &weights,
+            goal,
+            start: start_date,
+            end: end_date,
+            timezone,
+            width,
+            height,
+        })
+    })
+    .await
+    .map_err(|e| ApiError::Unexpected(Box::new(e)))?
+}
+
+/// Chart of the weights of the user with their trend and its goal, for dashboards and emails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/chart.svg",
+    tag = "users",
+    params(
+        ("id" = i64, Path),
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, 30 days before the end date by default"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, now by default"),
+        ("width" = Option<u32>, Query, minimum = 50, maximum = 4000, description = "Pixels, 800 by default"),
+        ("height" = Option<u32>, Query, minimum = 50, maximum = 4000, description = "Pixels, 400 by default"),
+    ),
+    responses(
+        (status = OK, content_type = "image/svg+xml", body = String),
+        (status = BAD_REQUEST, description = "Invalid dates or size", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_chart_svg(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let svg = chart(&state, id, &params, |chart| Ok(chart.svg().into_bytes())).await?;
+
+    Ok(([(CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+/// Same chart as `chart.svg` as a PNG image.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/chart.png",
+    tag = "users",
+    params(
+        ("id" = i64, Path),
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, 30 days before the end date by default"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, now by default"),
+        ("width" = Option<u32>, Query, minimum = 50, maximum = 4000, description = "Pixels, 800 by default"),
+        ("height" = Option<u32>, Query, minimum = 50, maximum = 4000, description = "Pixels, 400 by default"),
+    ),
+    responses(
+        (status = OK, content_type = "image/png", body = Vec<u8>),
+        (status = BAD_REQUEST, description = "Invalid dates or size", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_chart_png(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let png = chart(&state, id, &params, |chart| chart.png()).await?;
+
+    Ok(([(CONTENT_TYPE, "image/png")], png))
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::error::ApiError;
+
+type Date = DateTime<FixedOffset>;
+
+/// `start_date` and `end_date` query parameters in RFC 3339, either one can be missing but the
+/// start cannot be after the end when both are there.
+pub fn optional_range(
+    params: &HashMap<String, String>,
+) -> Result<(Option<Date>, Option<Date>), ApiError> {
+    let date = |name: &'static str| {
+        params
+            .get(name)
+            .map(|d| Date::parse_from_rfc3339(d).map_err(|_| ApiError::InvalidDateTime(name)))
+            .transpose()
+    };
+
+    let start_date = date("start_date")?;
+    let end_date = date("end_date")?;
+    if let (Some(start_date), Some(end_date)) = (start_date, end_date) {
+        if start_date > end_date {
+            return Err(ApiError::StartDateGreaterThanEndDate);
+        }
+    }
+
+    Ok((start_date, end_date))
+}
+
+/// Same range with the end now when it is missing, and the start given by `default_start` from the
+/// end.
+pub fn range(
+    params: &HashMap<String, String>,
+    default_start: impl FnOnce(Date) -> Date,
+) -> Result<(Date, Date), ApiError> {
+    let (start_date, end_date) = optional_range(params)?;
+
+    let end_date = end_date.unwrap_or_else(|| Utc::now().fixed_offset());
+    let start_date = start_date.unwrap_or_else(|| default_start(end_date));
+    // A start in the future is after the default end
+    if start_date > end_date {
+        return Err(ApiError::StartDateGreaterThanEndDate);
+    }
+
+    Ok((start_date, end_date))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn missing_dates_get_their_defaults() {
+        let end = DateTime::parse_from_rfc3339("2026-10-19T08:00:00+02:00").unwrap();
+
+        let (start_date, end_date) = range(
+            &params(&[("end_date", "2026-10-19T08:00:00+02:00")]),
+            |end| end - Duration::days(30),
+        )
+        .unwrap();
+
+        assert_eq!(end_date, end);
+        assert_eq!(start_date, end - Duration::days(30));
+        assert_eq!(optional_range(&params(&[])).unwrap(), (None, None));
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        assert!(matches!(
+            optional_range(&params(&[("start_date", "yesterday")])),
+            Err(ApiError::InvalidDateTime("start_date"))
+        ));
+        assert!(matches!(
+            optional_range(&params(&[
+                ("start_date", "2026-10-19T08:00:00Z"),
+                ("end_date", "2026-10-18T08:00:00Z")
+            ])),
+            Err(ApiError::StartDateGreaterThanEndDate)
+        ));
+        assert!(matches!(
+            range(
+                &params(&[("start_date", "2999-01-01T00:00:00Z")]),
+                std::convert::identity
+            ),
+            Err(ApiError::StartDateGreaterThanEndDate)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::app_state::AppState;
+use crate::domain::digest::{DigestFrequency, DigestSettings};
+use crate::domain::reminder;
+use crate::domain::user::UserId;
+use crate::error::ApiError;
+
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+pub fn digests(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/users/{id}/digest",
+            get(get_digest).put(update_digest).delete(delete_digest),
+        )
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_digest, update_digest, delete_digest))]
+pub struct DigestsApi;
+
+#[derive(Deserialize, ToSchema)]
+struct PutDigest {
+    /// Address the digests are emailed to.
+    email: String,
+    /// `weekly`, from Monday to Sunday, or `monthly`, weekly when missing.
+    frequency: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct DigestResponse {
+    email: String,
+    frequency: &'static str,
+    last_sent_at: Option<DateTime<Utc>>,
+}
+
+impl From<DigestSettings> for DigestResponse {
+    fn from(settings: DigestSettings) -> Self {
+        DigestResponse {
+            email: settings.email.to_string(),
+            frequency: settings.frequency.name(),
+            last_sent_at: settings.last_sent_at,
+        }
+    }
+}
+
+async fn find_user_id(state: &AppState, id: i64) -> Result<UserId, ApiError> {
+    Ok(state
+        .repository
+        .find_user(&UserId::new(id))
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/digest",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, body = DigestResponse),
+        (status = NOT_FOUND, description = "User not found or without digest", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_digest(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<DigestResponse>, ApiError> {
+    let user_id = find_user_id(&state, id).await?;
+
+    let settings = state
+        .repository
+        .find_digest_settings(&user_id)
+        .await?
+        .ok_or(ApiError::DigestNotFound)?;
+
+    Ok(Json(settings.into()))
+}
+
+/// Emails the user a summary of its weights after every week or month, replacing its previous
+/// settings.
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}/digest",
+    tag = "users",
+    params(("id" = i64, Path)),
+    request_body = PutDigest,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid email or frequency", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn update_digest(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<PutDigest>,
+) -> Result<StatusCode, ApiError> {
+    let email = reminder::parse_email(&body.email)?;
+    let frequency = body
+        .frequency
+        .as_deref()
+        .map(DigestFrequency::parse)
+        .transpose()?
+        .unwrap_or(DigestFrequency::Weekly);
+
+    let user_id = find_user_id(&state, id).await?;
+
+    state
+        .repository
+        .save_digest_settings(&DigestSettings {
+            user_id,
+            email,
+            frequency,
+            last_sent_at: None,
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stops sending digests to the user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}/digest",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "User not found or without digest", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn delete_digest(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = find_user_id(&state, id).await?;
+
+    state.repository.delete_digest_settings(&user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
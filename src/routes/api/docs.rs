@@ -0,0 +1,76 @@
+use axum::routing::get;
+use axum::{Json, Router};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::OpenApi as OpenApiDocument;
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use super::admin::AdminApi;
+use super::charts::ChartsApi;
+use super::digests::DigestsApi;
+use super::events::EventsApi;
+use super::measurements::MeasurementsApi;
+use super::problem::{FieldError, Problem};
+use super::reminders::RemindersApi;
+use super::reports::ReportsApi;
+use super::users::UsersApi;
+use super::webhooks::WebhooksApi;
+
+const OPENAPI_PATH: &str = "/api/openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+    modifiers(&AdminToken),
+    components(schemas(Problem, FieldError)),
+    tags(
+        (name = "users"),
+        (name = "measurements", description = "Weights and impedances of the users"),
+        (name = "admin", description = "Bearer `ADMIN_TOKEN`, disabled when it is not configured"),
+    )
+)]
+struct ApiDoc;
+
+struct AdminToken;
+
+impl Modify for AdminToken {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+/// OpenAPI document of every endpoint under `/api/v1`.
+pub fn openapi() -> OpenApiDocument {
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(UsersApi::openapi());
+    openapi.merge(MeasurementsApi::openapi());
+    openapi.merge(EventsApi::openapi());
+    openapi.merge(AdminApi::openapi());
+    openapi.merge(WebhooksApi::openapi());
+    openapi.merge(RemindersApi::openapi());
+    openapi.merge(DigestsApi::openapi());
+    openapi.merge(ChartsApi::openapi());
+    openapi.merge(ReportsApi::openapi());
+
+    openapi
+}
+
+/// The OpenAPI document and a Swagger UI to browse it, bundled in the binary, both under `/api`
+/// but outside of its router so that the UI links to its own files.
+pub fn docs() -> Router {
+    let openapi = openapi();
+
+    Router::new()
+        .route(
+            OPENAPI_PATH,
+            get(move || {
+                let openapi = openapi.clone();
+                async move { Json(openapi) }
+            }),
+        )
+        .merge(SwaggerUi::new("/api/docs").config(Config::from(OPENAPI_PATH)))
+}
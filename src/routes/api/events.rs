@@ -0,0 +1,72 @@
+use std::convert::Infallible;
+
+use axum::extract::{Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::get;
+use axum::Router;
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use utoipa::OpenApi;
+
+use crate::app_state::AppState;
+use crate::domain::user::UserId;
+use crate::error::ApiError;
+use crate::events::Event::{GoalReached, Measurement};
+use crate::events::Notification;
+
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+pub fn events(state: AppState) -> Router {
+    Router::new()
+        .route("/users/{id}/events", get(get_events))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_events))]
+pub struct EventsApi;
+
+/// Server-sent events with the changes to the measurements of the user made from now on.
+///
+/// Each `measurement` event holds the `user_id`, the `measurement` (`weight` or `impedance`), its
+/// `id`, the `change` (`inserted` or `deleted`), `measured_at`, `value` and `unit`. A `goal` event
+/// tells that a new weight crossed the goal of the user. A `lagged` event tells that some were
+/// missed and the measurements have to be read again.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/events",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, content(("text/event-stream"))),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_events(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let user_id: UserId = state
+        .repository
+        .find_user(&UserId::new(id))
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id;
+
+    let notifications = state.events.subscribe((&user_id).into());
+    let events = notifications.map(|notification| {
+        Ok(match notification {
+            Notification::Event(Measurement(event)) => Event::default()
+                .event("measurement")
+                .data(json!(event).to_string()),
+            Notification::Event(GoalReached(event)) => Event::default()
+                .event("goal")
+                .data(json!(event).to_string()),
+            Notification::Lagged(missed) => Event::default()
+                .event("lagged")
+                .data(json!({ "missed": missed }).to_string()),
+        })
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
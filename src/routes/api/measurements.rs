@@ -1,49 +1,65 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use axum::extract::{Path, Query};
-use axum::http::header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::body::Body;
+use axum::extract::{OriginalUri, Path, Query};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE, LINK, VARY};
+use axum::http::response::Builder;
 use axum::http::{HeaderMap, Response, StatusCode};
 use axum::routing::{delete, get};
 use axum::Router;
 use axum::{extract::State, Json};
-use chrono::{DateTime, FixedOffset, Local};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
+use chrono::{DateTime, FixedOffset};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
 
 use crate::app_state::AppState;
 use crate::domain::impedance::{Impedance, ImpedanceId, Ohms};
+use crate::domain::page::{self, Cursor, PageRequest, SortOrder, DEFAULT_PAGE_LIMIT};
 use crate::domain::user::UserId;
 use crate::domain::weight::{Kilograms, Weight, WeightId};
 use crate::error::ApiError;
-use crate::repositories;
+use crate::events::{Change, GoalEvent, MeasurementEvent};
+use crate::export::{self, Exportable};
+use crate::telemetry;
 
-#[derive(Deserialize)]
+use super::dates;
+use super::negotiation::{self, Format};
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+#[derive(Deserialize, ToSchema)]
 struct PostWeight {
     user_id: i64,
+    /// RFC 3339 date time, the offset is kept.
+    #[schema(format = DateTime)]
     measured_at: String,
+    /// Cannot be negative.
     kilograms: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct PostImpedance {
     user_id: i64,
+    /// RFC 3339 date time, the offset is kept.
+    #[schema(format = DateTime)]
     measured_at: String,
     ohms: f64,
 }
 
-#[derive(Serialize)]
-struct WeightResponse {
-    weight_id: i64,
-    measured_at: String,
-    kilograms: f64,
-}
-
-#[derive(Serialize)]
-struct ImpedanceResponse {
-    impedance_id: i64,
-    measured_at: String,
-    ohms: f64,
-}
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_weights,
+        add_weight,
+        delete_weight,
+        get_impedances,
+        add_impedance,
+        delete_impedance
+    ),
+    components(schemas(Weight, Impedance))
+)]
+pub struct MeasurementsApi;
 
 pub fn measurements(state: AppState) -> Router {
     Router::new()
@@ -60,37 +76,98 @@ pub fn measurements(state: AppState) -> Router {
         .with_state(state)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/measurements/weights",
+    tag = "measurements",
+    request_body = PostWeight,
+    responses(
+        (status = CREATED),
+        (status = BAD_REQUEST, description = "Invalid date or weight", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn add_weight(
     State(state): State<AppState>,
     Json(body): Json<PostWeight>,
 ) -> Result<StatusCode, ApiError> {
     let user_id: UserId = UserId::new(body.user_id);
 
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
+    let user = state
+        .repository
+        .find_user(&user_id)
         .await?
-        .ok_or(ApiError::UserNotFound)?
-        .id;
+        .ok_or(ApiError::UserNotFound)?;
 
     let measured_at = body
         .measured_at
         .parse::<DateTime<FixedOffset>>()
-        .map_err(|_| ApiError::InvalidDateTime)?;
+        .map_err(|_| ApiError::InvalidDateTime("measured_at"))?;
 
     let kilograms = Kilograms::try_from(body.kilograms)?;
 
-    repositories::measurements::insert_weight(&state.pool, &user_id, &measured_at, &kilograms)
+    let previous = state.repository.find_latest_weight(&user.id).await?;
+
+    let weight_id = state
+        .repository
+        .insert_weight(&user.id, &measured_at, &kilograms)
         .await?;
+    telemetry::record_ingestion("weight", "api");
+
+    let weight = Weight {
+        weight_id,
+        user_id: UserId::new(body.user_id),
+        measured_at,
+        kilograms,
+    };
+    state.events.publish(MeasurementEvent::new(
+        body.user_id,
+        &weight,
+        Change::Inserted,
+    ));
+
+    // A backfilled weight older than the latest one does not change where the user stands
+    let reached = previous.is_some_and(|previous| {
+        previous.measured_at <= weight.measured_at
+            && user.reaches_goal(&previous.kilograms, &weight.kilograms)
+    });
+    if let (true, Some(goal)) = (reached, &user.goal) {
+        state.events.publish(GoalEvent {
+            user_id: body.user_id,
+            goal_kilograms: goal.into(),
+            weight_id: weight.id(),
+            measured_at: weight.measured_at,
+            kilograms: weight.value(),
+        });
+    }
+
+    if let Some(mqtt) = &state.mqtt {
+        mqtt.publish_user_in_background(state.repository.clone(), user.id);
+    }
 
     Ok(StatusCode::CREATED)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/measurements/impedances",
+    tag = "measurements",
+    request_body = PostImpedance,
+    responses(
+        (status = CREATED),
+        (status = BAD_REQUEST, description = "Invalid date or impedance", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn add_impedance(
     State(state): State<AppState>,
     Json(body): Json<PostImpedance>,
 ) -> Result<StatusCode, ApiError> {
     let user_id: UserId = UserId::new(body.user_id);
 
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
+    let user_id = state
+        .repository
+        .find_user(&user_id)
         .await?
         .ok_or(ApiError::UserNotFound)?
         .id;
@@ -98,215 +175,415 @@ async fn add_impedance(
     let measured_at = body
         .measured_at
         .parse::<DateTime<FixedOffset>>()
-        .map_err(|_| ApiError::InvalidDateTime)?;
+        .map_err(|_| ApiError::InvalidDateTime("measured_at"))?;
 
     let ohms = Ohms::try_from(body.ohms)?;
 
-    repositories::measurements::insert_impedance(&state.pool, &user_id, &measured_at, &ohms)
+    let impedance_id = state
+        .repository
+        .insert_impedance(&user_id, &measured_at, &ohms)
         .await?;
+    telemetry::record_ingestion("impedance", "api");
+
+    let impedance = Impedance {
+        impedance_id,
+        user_id,
+        measured_at,
+        ohms,
+    };
+    state.events.publish(MeasurementEvent::new(
+        (&impedance.user_id).into(),
+        &impedance,
+        Change::Inserted,
+    ));
 
     Ok(StatusCode::CREATED)
 }
 
+/// Lists the weights of a user.
+///
+/// Everything between `start_date` and `end_date` is returned at once, unless `limit` or `cursor`
+/// is given: then a page is returned, with the dates optional, and the next one is linked in the
+/// `Link` header. The format follows the `Accept` header, quality values and wildcards included,
+/// or the `format` parameter, which takes precedence.
+#[utoipa::path(
+    get,
+    path = "/api/v1/measurements/weights",
+    tag = "measurements",
+    params(
+        ("user_id" = i64, Query),
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, mandatory without `limit` or `cursor`"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, mandatory without `limit` or `cursor`"),
+        ("limit" = Option<usize>, Query, minimum = 1, maximum = 1000, description = "Size of the page, 100 by default"),
+        ("cursor" = Option<String>, Query, description = "Where the page starts, as linked from the previous one"),
+        ("order" = Option<String>, Query, description = "`asc`, the default, or `desc`"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `ndjson`, `xlsx` or `parquet`, overrides the Accept header"),
+    ),
+    responses(
+        (status = OK, description = "The weights, oldest first unless `order=desc`",
+            content(
+                (Vec<Weight> = "application/json"),
+                ("text/csv"),
+                ("application/x-ndjson"),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+                ("application/vnd.apache.parquet"),
+            ),
+            headers(("link" = String, description = "`first` and `next` pages, only when paging"))
+        ),
+        (status = BAD_REQUEST, description = "Missing or invalid parameters", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_ACCEPTABLE, description = "None of the accepted formats can be produced", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn get_weights(
     State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<HashMap<String, String>>,
     headers: HeaderMap,
-) -> Result<Response<String>, ApiError> {
+) -> Result<Response<Body>, ApiError> {
     let user_id: UserId = UserId::new(match params.get("user_id") {
         Some(id) => id.parse().map_err(|_| ApiError::InvalidUserId)?,
         None => return Err(ApiError::MandatoryUserId),
     });
 
-    let start_date: DateTime<FixedOffset> = match params.get("start_date") {
-        Some(d) => {
-            DateTime::<FixedOffset>::parse_from_rfc3339(d).map_err(|_| ApiError::InvalidDateTime)?
-        }
-        None => return Err(ApiError::MandatoryStartDate),
-    };
-
-    let end_date: DateTime<FixedOffset> = match params.get("end_date") {
-        Some(d) => {
-            DateTime::<FixedOffset>::parse_from_rfc3339(d).map_err(|_| ApiError::InvalidDateTime)?
-        }
-        None => return Err(ApiError::MandatoryEndDate),
-    };
+    let listing = listing(&params)?;
+    let format = negotiation::negotiate(&headers, &params, MEASUREMENT_FORMATS)?;
 
-    if start_date > end_date {
-        return Err(ApiError::StartDateGreaterThanEndDate);
-    }
-
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
+    let user_id = state
+        .repository
+        .find_user(&user_id)
         .await?
         .ok_or(ApiError::UserNotFound)?
         .id;
 
-    let weights: Vec<WeightResponse> = repositories::measurements::find_weights_between_dates(
-        &state.pool,
-        &user_id,
-        &start_date,
-        &end_date,
-    )
-    .await?
-    .into_iter()
-    .map(|w: Weight| WeightResponse {
-        weight_id: w.weight_id.into(),
-        measured_at: DateTime::<Local>::from(w.measured_at).to_rfc3339(),
-        kilograms: w.kilograms.into(),
-    })
-    .collect();
-
-    if let Some(accept_encoding_header) = headers.get(ACCEPT) {
-        match accept_encoding_header.to_str().unwrap() {
-            "text/csv" => {
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header(CONTENT_TYPE, "text/csv")
-                    .header(CONTENT_DISPOSITION, "attachment; filename=\"weights.csv\"")
-                    .body(generate_weights_csv(weights))
-                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
-
-                Ok(response)
+    let (weights, link): (BoxStream<'static, Result<Weight, ApiError>>, Option<String>) =
+        match listing {
+            Listing::Range(start_date, end_date) => (
+                state
+                    .repository
+                    .stream_weights_between_dates(&user_id, &start_date, &end_date),
+                None,
+            ),
+            Listing::Page(page) => {
+                let limit = page.limit;
+                let page = PageRequest {
+                    limit: limit + 1,
+                    ..page
+                };
+
+                let mut weights = state.repository.find_weights_page(&user_id, &page).await?;
+                let next = next_cursor(&mut weights, limit, |measurement| {
+                    Cursor::new(&measurement.measured_at, (&measurement.weight_id).into())
+                });
+
+                (
+                    stream::iter(weights.into_iter().map(Ok)).boxed(),
+                    Some(link_header(uri.path(), &params, next.as_ref())),
+                )
             }
-            "application/json" => {
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header(CONTENT_TYPE, "application/json")
-                    .body(json!(weights).to_string())
-                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
-
-                Ok(response)
-            }
-            _ => Err(ApiError::UnsupportedMediaType),
-        }
-    } else {
-        Err(ApiError::UnsupportedMediaType)
+        };
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(VARY, "accept");
+    if let Some(link) = link {
+        builder = builder.header(LINK, link);
     }
-}
 
-fn generate_weights_csv(weights: Vec<WeightResponse>) -> String {
-    weights.iter().fold(
-        "weight_id,measured_at,kilograms".to_string(),
-        |mut acc, weight| {
-            let row = format!(
-                "\n{},{},{}",
-                weight.weight_id, weight.measured_at, weight.kilograms
-            );
-            acc.push_str(row.as_str());
-            acc
-        },
-    )
+    let body = export_body(format, weights).await?;
+
+    attachment(builder, format, "weights")
+        .body(body)
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
 }
 
+/// Lists the impedances of a user.
+///
+/// Everything between `start_date` and `end_date` is returned at once, unless `limit` or `cursor`
+/// is given: then a page is returned, with the dates optional, and the next one is linked in the
+/// `Link` header. The format follows the `Accept` header, quality values and wildcards included,
+/// or the `format` parameter, which takes precedence.
+#[utoipa::path(
+    get,
+    path = "/api/v1/measurements/impedances",
+    tag = "measurements",
+    params(
+        ("user_id" = i64, Query),
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, mandatory without `limit` or `cursor`"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, mandatory without `limit` or `cursor`"),
+        ("limit" = Option<usize>, Query, minimum = 1, maximum = 1000, description = "Size of the page, 100 by default"),
+        ("cursor" = Option<String>, Query, description = "Where the page starts, as linked from the previous one"),
+        ("order" = Option<String>, Query, description = "`asc`, the default, or `desc`"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `ndjson`, `xlsx` or `parquet`, overrides the Accept header"),
+    ),
+    responses(
+        (status = OK, description = "The impedances, oldest first unless `order=desc`",
+            content(
+                (Vec<Impedance> = "application/json"),
+                ("text/csv"),
+                ("application/x-ndjson"),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+                ("application/vnd.apache.parquet"),
+            ),
+            headers(("link" = String, description = "`first` and `next` pages, only when paging"))
+        ),
+        (status = BAD_REQUEST, description = "Missing or invalid parameters", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_ACCEPTABLE, description = "None of the accepted formats can be produced", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn get_impedances(
     State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<HashMap<String, String>>,
     headers: HeaderMap,
-) -> Result<Response<String>, ApiError> {
+) -> Result<Response<Body>, ApiError> {
     let user_id: UserId = UserId::new(match params.get("user_id") {
         Some(id) => id.parse().map_err(|_| ApiError::InvalidUserId)?,
         None => return Err(ApiError::MandatoryUserId),
     });
 
-    let start_date: DateTime<FixedOffset> = match params.get("start_date") {
-        Some(d) => {
-            DateTime::<FixedOffset>::parse_from_rfc3339(d).map_err(|_| ApiError::InvalidDateTime)?
+    let listing = listing(&params)?;
+    let format = negotiation::negotiate(&headers, &params, MEASUREMENT_FORMATS)?;
+
+    let user_id = state
+        .repository
+        .find_user(&user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id;
+
+    let (impedances, link): (
+        BoxStream<'static, Result<Impedance, ApiError>>,
+        Option<String>,
+    ) = match listing {
+        Listing::Range(start_date, end_date) => (
+            state
+                .repository
+                .stream_impedances_between_dates(&user_id, &start_date, &end_date),
+            None,
+        ),
+        Listing::Page(page) => {
+            let limit = page.limit;
+            let page = PageRequest {
+                limit: limit + 1,
+                ..page
+            };
+
+            let mut impedances = state
+                .repository
+                .find_impedances_page(&user_id, &page)
+                .await?;
+            let next = next_cursor(&mut impedances, limit, |measurement| {
+                Cursor::new(&measurement.measured_at, (&measurement.impedance_id).into())
+            });
+
+            (
+                stream::iter(impedances.into_iter().map(Ok)).boxed(),
+                Some(link_header(uri.path(), &params, next.as_ref())),
+            )
         }
-        None => return Err(ApiError::MandatoryStartDate),
     };
 
-    let end_date: DateTime<FixedOffset> = match params.get("end_date") {
-        Some(d) => {
-            DateTime::<FixedOffset>::parse_from_rfc3339(d).map_err(|_| ApiError::InvalidDateTime)?
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(VARY, "accept");
+    if let Some(link) = link {
+        builder = builder.header(LINK, link);
+    }
+
+    let body = export_body(format, impedances).await?;
+
+    attachment(builder, format, "impedances")
+        .body(body)
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+/// Body in the format, streamed as the measurements are read for the text formats and built at
+/// once for the binary ones.
+async fn export_body<T: Exportable>(
+    format: Format,
+    measurements: BoxStream<'static, Result<T, ApiError>>,
+) -> Result<Body, ApiError> {
+    let stream = match format {
+        Format::Json => export::json_stream(measurements),
+        Format::Csv => export::csv_stream(measurements),
+        Format::Ndjson => export::ndjson_stream(measurements),
+        Format::Xlsx => {
+            let measurements: Vec<T> = measurements.try_collect().await?;
+            return Ok(export::workbook(&measurements)?.into());
+        }
+        Format::Parquet => {
+            let measurements: Vec<T> = measurements.try_collect().await?;
+            return Ok(export::parquet(&measurements)?.into());
         }
-        None => return Err(ApiError::MandatoryEndDate),
     };
 
-    if start_date > end_date {
-        return Err(ApiError::StartDateGreaterThanEndDate);
+    Ok(streamed(stream))
+}
+
+/// Body sent as the chunks of the stream are produced.
+pub(super) fn streamed(stream: BoxStream<'static, Result<String, ApiError>>) -> Body {
+    // The status is already sent once the body streams, failures can only cut it short
+    Body::from_stream(stream.inspect_err(|error| {
+        tracing::error!(%error, "Measurements export failed while streaming");
+    }))
+}
+
+const MEASUREMENT_FORMATS: &[Format] = &[
+    Format::Json,
+    Format::Csv,
+    Format::Ndjson,
+    Format::Xlsx,
+    Format::Parquet,
+];
+
+/// Sets the content type, and the file name for the formats meant to be downloaded.
+pub(super) fn attachment(builder: Builder, format: Format, name: &str) -> Builder {
+    let builder = builder.header(CONTENT_TYPE, format.media_type());
+
+    match format {
+        Format::Json | Format::Ndjson => builder,
+        Format::Csv | Format::Xlsx | Format::Parquet => builder.header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", name, format.name()),
+        ),
     }
+}
 
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
-        .await?
-        .ok_or(ApiError::UserNotFound)?
-        .id;
+enum Listing {
+    /// Everything between both mandatory dates.
+    Range(DateTime<FixedOffset>, DateTime<FixedOffset>),
+    /// A page, as soon as a limit or a cursor is given.
+    Page(PageRequest),
+}
 
-    let impedances: Vec<ImpedanceResponse> =
-        repositories::measurements::find_impedances_between_dates(
-            &state.pool,
-            &user_id,
-            &start_date,
-            &end_date,
-        )
-        .await?
-        .into_iter()
-        .map(|i: Impedance| ImpedanceResponse {
-            impedance_id: i.impedance_id.into(),
-            measured_at: DateTime::<Local>::from(i.measured_at).to_rfc3339(),
-            ohms: i.ohms.into(),
-        })
-        .collect();
-
-    if let Some(accept_encoding_header) = headers.get(ACCEPT) {
-        match accept_encoding_header.to_str().unwrap() {
-            "text/csv" => {
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header(CONTENT_TYPE, "text/csv")
-                    .header(CONTENT_DISPOSITION, "attachment; filename=\"weights.csv\"")
-                    .body(generate_impedances_csv(impedances))
-                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
-
-                Ok(response)
-            }
-            "application/json" => {
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header(CONTENT_TYPE, "application/json")
-                    .body(json!(impedances).to_string())
-                    .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
-
-                Ok(response)
-            }
-            _ => Err(ApiError::UnsupportedMediaType),
-        }
-    } else {
-        Err(ApiError::UnsupportedMediaType)
+fn listing(params: &HashMap<String, String>) -> Result<Listing, ApiError> {
+    let (start_date, end_date) = dates::optional_range(params)?;
+
+    if !params.contains_key("limit") && !params.contains_key("cursor") {
+        return Ok(Listing::Range(
+            start_date.ok_or(ApiError::MandatoryStartDate)?,
+            end_date.ok_or(ApiError::MandatoryEndDate)?,
+        ));
     }
+
+    Ok(Listing::Page(PageRequest {
+        start_date,
+        end_date,
+        order: params
+            .get("order")
+            .map(|order| SortOrder::parse(order))
+            .transpose()?
+            .unwrap_or(SortOrder::Ascending),
+        after: params
+            .get("cursor")
+            .map(|cursor| Cursor::parse(cursor))
+            .transpose()?,
+        limit: params
+            .get("limit")
+            .map(|limit| page::parse_limit(limit))
+            .transpose()?
+            .unwrap_or(DEFAULT_PAGE_LIMIT),
+    }))
 }
 
-fn generate_impedances_csv(impedances: Vec<ImpedanceResponse>) -> String {
-    impedances.iter().fold(
-        "impedance_id,measured_at,ohms".to_string(),
-        |mut acc, weight| {
-            let row = format!(
-                "\n{},{},{}",
-                weight.impedance_id, weight.measured_at, weight.ohms
-            );
-            acc.push_str(row.as_str());
-            acc
-        },
-    )
+/// Trims the one measurement fetched past the limit, which tells whether there is a next page.
+fn next_cursor<T>(
+    measurements: &mut Vec<T>,
+    limit: usize,
+    cursor: impl Fn(&T) -> Cursor,
+) -> Option<Cursor> {
+    if measurements.len() <= limit {
+        return None;
+    }
+
+    measurements.truncate(limit);
+    measurements.last().map(cursor)
 }
 
+/// `Link` header with the first page and, when there is one, the next page of the listing.
+fn link_header(path: &str, params: &HashMap<String, String>, next: Option<&Cursor>) -> String {
+    let link = |cursor: Option<String>, rel: &str| {
+        let mut query: BTreeMap<&str, String> = params
+            .iter()
+            .filter(|(name, _)| *name != "cursor")
+            .map(|(name, value)| (name.as_str(), value.clone()))
+            .collect();
+        query
+            .entry("limit")
+            .or_insert_with(|| DEFAULT_PAGE_LIMIT.to_string());
+        if let Some(cursor) = cursor {
+            query.insert("cursor", cursor);
+        }
+
+        let query = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query)
+            .finish();
+        format!("<{}?{}>; rel=\"{}\"", path, query, rel)
+    };
+
+    let mut links = vec![link(None, "first")];
+    if let Some(next) = next {
+        links.push(link(Some(next.to_string()), "next"));
+    }
+
+    links.join(", ")
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/measurements/weights/{weight_id}",
+    tag = "measurements",
+    params(("weight_id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "Weight not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn delete_weight(
     State(state): State<AppState>,
     Path(weight_id): Path<i64>,
 ) -> Result<StatusCode, ApiError> {
     let weight_id = WeightId::new(weight_id);
 
-    repositories::measurements::delete_weight(&state.pool, &weight_id).await?;
+    let weight = state.repository.delete_weight(&weight_id).await?;
+    state.events.publish(MeasurementEvent::new(
+        (&weight.user_id).into(),
+        &weight,
+        Change::Deleted,
+    ));
+
+    // The latest weight may be the deleted one
+    if let Some(mqtt) = &state.mqtt {
+        mqtt.publish_user_in_background(state.repository.clone(), weight.user_id);
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/measurements/impedances/{impedance_id}",
+    tag = "measurements",
+    params(("impedance_id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "Impedance not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn delete_impedance(
     State(state): State<AppState>,
     Path(impedance_id): Path<i64>,
 ) -> Result<StatusCode, ApiError> {
     let impedance_id = ImpedanceId::new(impedance_id);
 
-    repositories::measurements::delete_impedance(&state.pool, &impedance_id).await?;
+    let impedance = state.repository.delete_impedance(&impedance_id).await?;
+    state.events.publish(MeasurementEvent::new(
+        (&impedance.user_id).into(),
+        &impedance,
+        Change::Deleted,
+    ));
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use axum::http::{header::ACCEPT, HeaderMap};
+
+use crate::error::ApiError;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Json,
+    Csv,
+    Ndjson,
+    Xlsx,
+    Parquet,
+}
+
+impl Format {
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    /// Value of the `format` query parameter that selects it, also used as file extension.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Xlsx => "xlsx",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// A media range of the `Accept` header, such as `text/*;q=0.5`.
+struct MediaRange<'a> {
+    main_type: &'a str,
+    sub_type: &'a str,
+    quality: f32,
+}
+
+impl MediaRange<'_> {
+    /// How specifically the range names the media type, none if it does not match it.
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        let (main_type, sub_type) = media_type.split_once('/')?;
+
+        match (self.main_type, self.sub_type) {
+            ("*", "*") => Some(0),
+            (m, "*") if m.eq_ignore_ascii_case(main_type) => Some(1),
+            (m, s) if m.eq_ignore_ascii_case(main_type) && s.eq_ignore_ascii_case(sub_type) => {
+                Some(2)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse_accept(value: &str) -> Vec<MediaRange<'_>> {
+    value
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let (main_type, sub_type) = parts.next()?.split_once('/')?;
+            let (main_type, sub_type) = (main_type.trim(), sub_type.trim());
+            let quality = parts
+                .filter_map(|parameter| parameter.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map(|(_, value)| value.trim().parse::<f32>().ok())
+                .unwrap_or(Some(1.0))?;
+
+            let valid =
+                !main_type.is_empty() && !sub_type.is_empty() && (0.0..=1.0).contains(&quality);
+
+            valid.then_some(MediaRange {
+                main_type,
+                sub_type,
+                quality,
+            })
+        })
+        .collect()
+}
+
+/// Picks the format of the response among the available ones, in order of preference.
+///
+/// The `format` query parameter takes precedence over the `Accept` header, which is honoured with
+/// its quality values and wildcards. Without either of them the first available format is used.
+pub fn negotiate(
+    headers: &HeaderMap,
+    params: &HashMap<String, String>,
+    available: &[Format],
+) -> Result<Format, ApiError> {
+    if let Some(name) = params.get("format") {
+        return available
+            .iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or(ApiError::NotAcceptable);
+    }
+
+    let accept = headers
+        .get_all(ACCEPT)
+        .iter()
+        .map(|value| String::from_utf8_lossy(value.as_bytes()))
+        .collect::<Vec<_>>()
+        .join(",");
+    if accept.trim().is_empty() {
+        return available.first().copied().ok_or(ApiError::NotAcceptable);
+    }
+
+    let ranges = parse_accept(&accept);
+    let quality = |format: &Format| {
+        ranges
+            .iter()
+            .filter_map(|range| Some((range.specificity(format.media_type())?, range.quality)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality)
+            .unwrap_or(0.0)
+    };
+
+    // The earliest available format wins between equally acceptable ones
+    available
+        .iter()
+        .map(|format| (*format, quality(format)))
+        .filter(|(_, quality)| *quality > 0.0)
+        .fold(None, |best: Option<(Format, f32)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|(format, _)| format)
+        .ok_or(ApiError::NotAcceptable)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const AVAILABLE: &[Format] = &[Format::Json, Format::Csv, Format::Ndjson];
+
+    fn negotiate_accept(accept: &'static [u8]) -> Result<Format, ApiError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_bytes(accept).unwrap());
+
+        negotiate(&headers, &HashMap::new(), AVAILABLE)
+    }
+
+    #[test]
+    fn wildcards_and_missing_header_get_the_preferred_format() {
+        assert_eq!(negotiate_accept(b"*/*").unwrap(), Format::Json);
+        assert_eq!(negotiate_accept(b"text/*").unwrap(), Format::Csv);
+        assert_eq!(
+            negotiate_accept(b"application/*;q=0.5, application/x-ndjson").unwrap(),
+            Format::Ndjson
+        );
+        assert_eq!(
+            negotiate(&HeaderMap::new(), &HashMap::new(), AVAILABLE).unwrap(),
+            Format::Json
+        );
+    }
+
+    #[test]
+    fn quality_values_are_honoured() {
+        assert_eq!(
+            negotiate_accept(b"application/json;q=0.5, text/csv").unwrap(),
+            Format::Csv
+        );
+        assert_eq!(
+            negotiate_accept(b"application/json, text/plain;q=0.9").unwrap(),
+            Format::Json
+        );
+        assert_eq!(
+            negotiate_accept(b"*/*;q=0.1, application/json;q=0").unwrap(),
+            Format::Csv
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_requests_are_not_acceptable() {
+        assert!(negotiate_accept(b"text/html").is_err());
+        assert!(negotiate_accept(b"application/json;q=0").is_err());
+        assert!(negotiate_accept("text/html; charset=\u{e9}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn format_parameter_overrides_the_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        let params = |format: &str| HashMap::from([("format".to_string(), format.to_string())]);
+
+        assert_eq!(
+            negotiate(&headers, &params("csv"), AVAILABLE).unwrap(),
+            Format::Csv
+        );
+        assert!(negotiate(&headers, &params("xml"), AVAILABLE).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use axum::body::{self, Body};
+use axum::extract::{OriginalUri, Request};
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::ErrorDetails;
+
+pub const PROBLEM_MEDIA_TYPE: &str = "application/problem+json";
+
+/// Bodies of the errors that do not come from an `ApiError`, such as a malformed JSON body, are
+/// read up to this size to describe them.
+const MAX_REJECTION_BYTES: usize = 64 * 1024;
+
+/// RFC 7807 problem details, with the stable `code` of the error and the fields that failed
+/// validation.
+#[derive(Serialize, ToSchema)]
+pub struct Problem {
+    /// Always `about:blank`, the `code` tells the errors apart.
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    /// Reason phrase of the status.
+    title: String,
+    status: u16,
+    detail: String,
+    /// Path of the request.
+    instance: String,
+    /// Such as `user_not_found` or `invalid_date_time`.
+    code: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FieldError {
+    /// Query parameter or field of the body.
+    field: String,
+    code: String,
+    detail: String,
+}
+
+/// Replaces the body of the error responses with problem details.
+pub async fn problem_details(request: Request, next: Next) -> Response {
+    let instance = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let response = next.run(request).await;
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let details = match parts.extensions.remove::<ErrorDetails>() {
+        Some(details) => details,
+        None => rejection_details(status, body).await,
+    };
+
+    let errors = details
+        .field
+        .map(|field| FieldError {
+            field: field.to_string(),
+            code: details.code.to_string(),
+            detail: details.detail.clone(),
+        })
+        .into_iter()
+        .collect();
+
+    let problem = Problem {
+        problem_type: "about:blank",
+        title: status.canonical_reason().unwrap_or_default().to_string(),
+        status: status.as_u16(),
+        detail: details.detail,
+        instance,
+        code: details.code.to_string(),
+        errors,
+    };
+
+    // Headers such as `Allow` still apply, only the body changes
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_MEDIA_TYPE));
+
+    (parts, Json(problem)).into_response()
+}
+
+/// Details of an error rejected by axum before reaching a handler, from its plain text body.
+async fn rejection_details(status: StatusCode, body: Body) -> ErrorDetails {
+    let reason = status.canonical_reason().unwrap_or("Error");
+    let detail = body::to_bytes(body, MAX_REJECTION_BYTES)
+        .await
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .filter(|detail| !detail.is_empty())
+        .unwrap_or_else(|| reason.to_string());
+
+    // The codes of the statuses never change, so these are as stable as the ones of `ApiError`
+    let code = match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        _ if status.is_client_error() => "client_error",
+        _ => "internal_error",
+    };
+
+    ErrorDetails {
+        code,
+        field: None,
+        detail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ApiError;
+
+    use super::*;
+
+    #[test]
+    fn api_errors_carry_their_code_and_field() {
+        let response = ApiError::InvalidDateTime("start_date").into_response();
+        let details = response.extensions().get::<ErrorDetails>().unwrap();
+
+        assert_eq!(details.code, "invalid_date_time");
+        assert_eq!(details.field, Some("start_date"));
+        assert_eq!(details.detail, "start_date must be a valid date");
+
+        let response = ApiError::UserNotFound.into_response();
+        assert_eq!(
+            response.extensions().get::<ErrorDetails>().unwrap().field,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn rejections_are_described_by_their_status() {
+        let details = rejection_details(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Body::from("missing field `kilograms`"),
+        )
+        .await;
+        assert_eq!(details.code, "unprocessable_entity");
+        assert_eq!(details.detail, "missing field `kilograms`");
+
+        let details = rejection_details(StatusCode::METHOD_NOT_ALLOWED, Body::empty()).await;
+        assert_eq!(details.detail, "Method Not Allowed");
+    }
+}
@@ -0,0 +1,185 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::app_state::AppState;
+use crate::domain::reminder::{self, QuietHours, ReminderSettings};
+use crate::domain::user::UserId;
+use crate::error::ApiError;
+
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+pub fn reminders(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/users/{id}/reminders",
+            get(get_reminders)
+                .put(update_reminders)
+                .delete(delete_reminders),
+        )
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_reminders, update_reminders, delete_reminders))]
+pub struct RemindersApi;
+
+#[derive(Deserialize, ToSchema)]
+struct PutReminders {
+    /// Address the reminders are emailed to, when SMTP is configured.
+    email: Option<String>,
+    /// Topic of the ntfy server the reminders are pushed to.
+    ntfy_topic: Option<String>,
+    /// Days without weighing in before the first reminder, the configured ones when missing.
+    after_days: Option<u32>,
+    /// Local time as `HH:MM` from which no reminders are sent, set together with the end.
+    quiet_hours_start: Option<String>,
+    /// Local time as `HH:MM` until which no reminders are sent, before the start to span midnight.
+    quiet_hours_end: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct RemindersResponse {
+    email: Option<String>,
+    ntfy_topic: Option<String>,
+    after_days: Option<u32>,
+    quiet_hours_start: Option<String>,
+    quiet_hours_end: Option<String>,
+    last_reminded_at: Option<DateTime<Utc>>,
+}
+
+impl From<ReminderSettings> for RemindersResponse {
+    fn from(settings: ReminderSettings) -> Self {
+        RemindersResponse {
+            email: settings.email.map(|email| email.to_string()),
+            ntfy_topic: settings.ntfy_topic,
+            after_days: settings.after_days,
+            quiet_hours_start: settings
+                .quiet_hours
+                .as_ref()
+                .map(|quiet_hours| reminder::format_time(&quiet_hours.start)),
+            quiet_hours_end: settings
+                .quiet_hours
+                .as_ref()
+                .map(|quiet_hours| reminder::format_time(&quiet_hours.end)),
+            last_reminded_at: settings.last_reminded_at,
+        }
+    }
+}
+
+async fn find_user_id(state: &AppState, id: i64) -> Result<UserId, ApiError> {
+    Ok(state
+        .repository
+        .find_user(&UserId::new(id))
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/reminders",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, body = RemindersResponse),
+        (status = NOT_FOUND, description = "User not found or without reminders", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_reminders(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<RemindersResponse>, ApiError> {
+    let user_id = find_user_id(&state, id).await?;
+
+    let settings = state
+        .repository
+        .find_reminder_settings(&user_id)
+        .await?
+        .ok_or(ApiError::RemindersNotFound)?;
+
+    Ok(Json(settings.into()))
+}
+
+/// Reminds the user to weigh in when it has not for a while, replacing its previous settings.
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}/reminders",
+    tag = "users",
+    params(("id" = i64, Path)),
+    request_body = PutReminders,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid email, topic, days or quiet hours", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn update_reminders(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<PutReminders>,
+) -> Result<StatusCode, ApiError> {
+    if body.after_days == Some(0) {
+        return Err(ApiError::InvalidReminderDays);
+    }
+    let email = body
+        .email
+        .as_deref()
+        .map(reminder::parse_email)
+        .transpose()?;
+    let ntfy_topic = body
+        .ntfy_topic
+        .as_deref()
+        .map(reminder::parse_ntfy_topic)
+        .transpose()?;
+    let quiet_hours = match (&body.quiet_hours_start, &body.quiet_hours_end) {
+        (Some(start), Some(end)) => Some(QuietHours {
+            start: reminder::parse_time(start)?,
+            end: reminder::parse_time(end)?,
+        }),
+        (None, None) => None,
+        _ => return Err(ApiError::InvalidQuietHours),
+    };
+
+    let user_id = find_user_id(&state, id).await?;
+
+    state
+        .repository
+        .save_reminder_settings(&ReminderSettings {
+            user_id,
+            after_days: body.after_days,
+            email,
+            ntfy_topic,
+            quiet_hours,
+            last_reminded_at: None,
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stops reminding the user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}/reminders",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "User not found or without reminders", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn delete_reminders(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = find_user_id(&state, id).await?;
+
+    state.repository.delete_reminder_settings(&user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
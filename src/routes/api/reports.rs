@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::{Duration, Utc};
+use utoipa::OpenApi;
+
+use crate::app_state::AppState;
+use crate::domain::user::UserId;
+use crate::error::ApiError;
+use crate::reports::{Report, MAX_NOTES_LENGTH};
+
+use super::dates;
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+/// Days covered up to the end date when there is no start date, about what is looked at between
+/// two visits to the doctor.
+const DEFAULT_DAYS: i64 = 90;
+
+pub fn reports(state: AppState) -> Router {
+    Router::new()
+        .route("/users/{id}/report.pdf", get(get_report_pdf))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_report_pdf))]
+pub struct ReportsApi;
+
+/// Printable report of the user between the dates with its profile, a chart of the weights, a
+/// summary of each month, the body composition when there are impedances and the notes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/report.pdf",
+    tag = "users",
+    params(
+        ("id" = i64, Path),
+        ("start_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, 90 days before the end date by default"),
+        ("end_date" = Option<String>, Query, format = DateTime, description = "RFC 3339, now by default"),
+        ("notes" = Option<String>, Query, description = "Printed at the end of the report, up to 2000 characters"),
+    ),
+    responses(
+        (status = OK, content_type = "application/pdf", body = Vec<u8>),
+        (status = BAD_REQUEST, description = "Invalid dates or notes", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_report_pdf(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (start_date, end_date) = dates::range(&params, |end| end - Duration::days(DEFAULT_DAYS))?;
+    let notes = params.get("notes").cloned();
+    if notes
+        .as_ref()
+        .is_some_and(|notes| notes.chars().count() > MAX_NOTES_LENGTH)
+    {
+        return Err(ApiError::InvalidReportNotes);
+    }
+
+    let user = state
+        .repository
+        .find_user(&UserId::new(id))
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    let weights = state
+        .repository
+        .find_weights_between_dates(&user.id, &start_date, &end_date)
+        .await?;
+    let impedances = state
+        .repository
+        .find_impedances_between_dates(&user.id, &start_date, &end_date)
+        .await?;
+    let timezone = user.timezone_or(state.timezone);
+
+    // Laying out the pages and rasterizing the chart keep the CPU busy for a while
+    let pdf = tokio::task::spawn_blocking(move || {
+        Report {
+            user: &user,
+            weights: &weights,
+            impedances: &impedances,
+            start: start_date,
+            end: end_date,
+            timezone,
+            notes: notes.as_deref(),
+            generated_at: Utc::now(),
+        }
+        .pdf()
+    })
+    .await
+    .map_err(|e| ApiError::Unexpected(Box::new(e)))??;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"weight-report-{}.pdf\"",
+                    end_date.with_timezone(&timezone).format("%Y-%m-%d")
+                ),
+            ),
+        ],
+        pdf,
+    ))
+}
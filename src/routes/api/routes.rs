@@ -1,11 +1,27 @@
+use axum::middleware;
 use axum::Router;
 
 use crate::app_state::AppState;
 
-use super::{measurements, users};
+use super::{
+    admin, charts, digests, events, measurements, problem, reminders, reports, users, webhooks,
+};
 
+/// Unversioned API, kept as it was before `/api/v1` for the existing clients.
 pub fn api(state: AppState) -> Router {
     Router::new()
         .merge(users::users(state.clone()))
         .merge(measurements::measurements(state.clone()))
+        .merge(events::events(state.clone()))
+        .merge(admin::admin(state.clone()))
+        .merge(webhooks::webhooks(state.clone()))
+        .merge(reminders::reminders(state.clone()))
+        .merge(digests::digests(state.clone()))
+        .merge(charts::charts(state.clone()))
+        .merge(reports::reports(state.clone()))
+}
+
+/// Same routes as `api`, answering the errors with problem details.
+pub fn api_v1(state: AppState) -> Router {
+    api(state).layer(middleware::from_fn(problem::problem_details))
 }
@@ -1,65 +1,304 @@
-use axum::extract::Path;
-use axum::http::StatusCode;
-use axum::routing::{delete, get};
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
 use axum::Router;
 use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::app_state::AppState;
-use crate::domain::user::{UserId, UserName};
+use crate::domain::user::{self, Centimeters, User, UserId, UserName};
+use crate::domain::weight::Kilograms;
 use crate::error::ApiError;
-use crate::repositories;
+use crate::events::{Change, MeasurementEvent};
+
+use super::negotiation::{self, Format};
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct PostUser {
     name: String,
+    /// Must be positive.
+    height_centimeters: Option<f64>,
+    /// IANA timezone the days of the user are split in, such as `Europe/Madrid`.
+    timezone: Option<String>,
+    /// Weight to reach, cannot be negative.
+    goal_kilograms: Option<f64>,
 }
 
 pub fn users(state: AppState) -> Router {
     Router::new()
         .route("/users", get(get_users).post(add_user))
-        .route("/users/{id}", delete(delete_user))
+        .route(
+            "/users/{id}",
+            get(get_user).put(update_user).delete(delete_user),
+        )
         .with_state(state)
 }
 
-async fn get_users(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
-    #[derive(Serialize)]
-    struct UserResponse {
-        id: i64,
-        name: String,
+#[derive(OpenApi)]
+#[openapi(paths(get_users, get_user, add_user, update_user, delete_user))]
+pub struct UsersApi;
+
+#[derive(Serialize, ToSchema)]
+struct UserResponse {
+    id: i64,
+    name: String,
+    height_centimeters: Option<f64>,
+    timezone: Option<String>,
+    goal_kilograms: Option<f64>,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        UserResponse {
+            id: user.id.into(),
+            name: user.name.into(),
+            height_centimeters: user.height.map(f64::from),
+            timezone: user.timezone.map(|timezone| timezone.name().to_string()),
+            goal_kilograms: user.goal.map(f64::from),
+        }
     }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    tag = "users",
+    params(("format" = Option<String>, Query, description = "`json`, overrides the Accept header")),
+    responses(
+        (status = OK, body = Vec<UserResponse>),
+        (status = NOT_ACCEPTABLE, description = "JSON is not accepted", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_users(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    negotiation::negotiate(&headers, &params, &[Format::Json])?;
 
-    let users: Vec<UserResponse> = repositories::users::find_users(&state.pool)
+    let users: Vec<UserResponse> = state
+        .repository
+        .find_users()
         .await?
         .into_iter()
-        .map(|u| UserResponse {
-            id: u.id.into(),
-            name: u.name.into(),
-        })
+        .map(UserResponse::from)
         .collect();
 
     Ok(Json(json!(users)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, body = UserResponse),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_user(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let id: UserId = UserId::new(id);
+
+    let user: UserResponse = state
+        .repository
+        .find_user(&id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?
+        .into();
+
+    Ok(Json(json!(user)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "users",
+    request_body = PostUser,
+    responses(
+        (status = CREATED),
+        (status = BAD_REQUEST, description = "Invalid height, timezone or goal", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn add_user(
     State(state): State<AppState>,
     Json(body): Json<PostUser>,
 ) -> Result<StatusCode, ApiError> {
     let name: UserName = UserName::new(body.name);
+    let height: Option<Centimeters> = body.height_centimeters.map(Centimeters::new).transpose()?;
+    let timezone: Option<Tz> = body
+        .timezone
+        .as_deref()
+        .map(user::parse_timezone)
+        .transpose()?;
+    let goal: Option<Kilograms> = body.goal_kilograms.map(Kilograms::new).transpose()?;
 
-    repositories::users::insert_user(&state.pool, &name).await?;
+    state
+        .repository
+        .insert_user(&name, height.as_ref(), timezone.as_ref(), goal.as_ref())
+        .await?;
 
     Ok(StatusCode::CREATED)
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(("id" = i64, Path)),
+    request_body = PostUser,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid height, timezone or goal", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn update_user(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<PostUser>,
+) -> Result<StatusCode, ApiError> {
+    let id: UserId = UserId::new(id);
+    let name: UserName = UserName::new(body.name);
+    let height: Option<Centimeters> = body.height_centimeters.map(Centimeters::new).transpose()?;
+    let timezone: Option<Tz> = body
+        .timezone
+        .as_deref()
+        .map(user::parse_timezone)
+        .transpose()?;
+    let goal: Option<Kilograms> = body.goal_kilograms.map(Kilograms::new).transpose()?;
+
+    state
+        .repository
+        .update_user(
+            &id,
+            &name,
+            height.as_ref(),
+            timezone.as_ref(),
+            goal.as_ref(),
+        )
+        .await?;
+
+    if let Some(mqtt) = &state.mqtt {
+        mqtt.publish_user_in_background(state.repository.clone(), id);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes the user together with all of its measurements.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(("id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
 async fn delete_user(
     State(state): State<AppState>,
     Path(id): Path<i64>,
 ) -> Result<StatusCode, ApiError> {
     let id: UserId = UserId::new(id);
 
-    repositories::users::delete_user(&state.pool, &id).await?;
+    // The measurements go away with the user, their subscribers are told about each of them
+    let (start_date, end_date) = (
+        DateTime::<Utc>::UNIX_EPOCH.fixed_offset(),
+        Utc::now().fixed_offset(),
+    );
+    let weights = state
+        .repository
+        .find_weights_between_dates(&id, &start_date, &end_date)
+        .await?;
+    let impedances = state
+        .repository
+        .find_impedances_between_dates(&id, &start_date, &end_date)
+        .await?;
+
+    state.repository.delete_user(&id).await?;
+
+    let user_id: i64 = (&id).into();
+    for weight in &weights {
+        state
+            .events
+            .publish(MeasurementEvent::new(user_id, weight, Change::Deleted));
+    }
+    for impedance in &impedances {
+        state
+            .events
+            .publish(MeasurementEvent::new(user_id, impedance, Change::Deleted));
+    }
+
+    if let Some(mqtt) = &state.mqtt {
+        mqtt.unpublish_user_in_background(id);
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+    use futures::StreamExt;
+
+    use crate::{
+        domain::impedance::Ohms,
+        events::{Event, Notification},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn deleting_a_user_deletes_its_measurements() {
+        let state = AppState::for_tests("sqlite::memory:", None).await;
+        state
+            .repository
+            .insert_user(&UserName::new("Ana".to_string()), None, None, None)
+            .await
+            .unwrap();
+        let user_id = UserId::new(1);
+        let measured_at =
+            DateTime::<FixedOffset>::parse_from_rfc3339("2026-10-19T08:00:00+02:00").unwrap();
+        state
+            .repository
+            .insert_weight(&user_id, &measured_at, &Kilograms::new(71.5).unwrap())
+            .await
+            .unwrap();
+        state
+            .repository
+            .insert_impedance(&user_id, &measured_at, &Ohms::new(520.0).unwrap())
+            .await
+            .unwrap();
+        let mut events = state.events.subscribe_all();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/users/{}",
+            listener.local_addr().unwrap(),
+            i64::from(&user_id)
+        );
+        tokio::spawn(async move { axum::serve(listener, users(state)).await });
+        let response = reqwest::Client::new().delete(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        for measurement in ["weight", "impedance"] {
+            let Some(Notification::Event(Event::Measurement(event))) = events.next().await else {
+                panic!("No {} event", measurement);
+            };
+            assert_eq!(event.measurement, measurement);
+            assert_eq!(event.change, Change::Deleted);
+        }
+    }
+}
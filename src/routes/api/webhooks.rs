@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::app_state::AppState;
+use crate::domain::user::UserId;
+use crate::domain::webhook::{Delivery, Webhook, WebhookEvent, WebhookId, WebhookUrl};
+use crate::error::ApiError;
+
+use super::admin::require_admin_token;
+use super::problem::{Problem, PROBLEM_MEDIA_TYPE};
+
+const DEFAULT_DELIVERIES_LIMIT: u32 = 50;
+const MAX_DELIVERIES_LIMIT: u32 = 1000;
+
+pub fn webhooks(state: AppState) -> Router {
+    Router::new()
+        .route("/admin/webhooks", get(get_webhooks).post(add_webhook))
+        .route(
+            "/admin/webhooks/{id}",
+            get(get_webhook).delete(delete_webhook),
+        )
+        .route("/admin/webhooks/{id}/deliveries", get(get_deliveries))
+        .route("/admin/webhooks/{id}/test", post(test_webhook))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_token,
+        ))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_webhooks,
+    add_webhook,
+    get_webhook,
+    delete_webhook,
+    get_deliveries,
+    test_webhook
+))]
+pub struct WebhooksApi;
+
+#[derive(Deserialize, ToSchema)]
+struct PostWebhook {
+    /// Absolute `http` or `https` URL the events are posted to.
+    url: String,
+    /// Only the events of this user are sent, the events of every user when it is missing.
+    user_id: Option<i64>,
+    /// `measurement.created`, `measurement.deleted`, `goal.reached` or `reminder.due`, all of them
+    /// when missing.
+    events: Option<Vec<String>>,
+    /// Key of the HMAC signatures, a random one is generated when missing.
+    secret: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct WebhookResponse {
+    id: i64,
+    url: String,
+    user_id: Option<i64>,
+    events: Vec<&'static str>,
+    /// Only returned when the webhook is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        WebhookResponse {
+            id: webhook.webhook_id.into(),
+            url: (&webhook.url).into(),
+            user_id: webhook.user_id.map(i64::from),
+            events: webhook.events.iter().map(WebhookEvent::name).collect(),
+            secret: None,
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct DeliveryResponse {
+    event: String,
+    /// Body that was posted.
+    payload: String,
+    attempt: u32,
+    attempted_at: DateTime<Utc>,
+    /// Status of the response, missing when none was received.
+    status_code: Option<u16>,
+    error: Option<String>,
+    succeeded: bool,
+}
+
+impl From<Delivery> for DeliveryResponse {
+    fn from(delivery: Delivery) -> Self {
+        DeliveryResponse {
+            event: delivery.event,
+            payload: delivery.payload,
+            attempt: delivery.attempt,
+            attempted_at: delivery.attempted_at,
+            status_code: delivery.status_code,
+            error: delivery.error,
+            succeeded: delivery.succeeded,
+        }
+    }
+}
+
+async fn find_webhook(state: &AppState, id: i64) -> Result<Webhook, ApiError> {
+    state
+        .repository
+        .find_webhook(&WebhookId::new(id))
+        .await?
+        .ok_or(ApiError::WebhookNotFound)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = OK, body = Vec<WebhookResponse>),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_webhooks(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<WebhookResponse>>, ApiError> {
+    let webhooks = state
+        .repository
+        .find_webhooks()
+        .await?
+        .into_iter()
+        .map(WebhookResponse::from)
+        .collect();
+
+    Ok(Json(webhooks))
+}
+
+/// Registers a webhook, the response holds its secret and it is the only one that does.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/webhooks",
+    tag = "admin",
+    security(("admin_token" = [])),
+    request_body = PostWebhook,
+    responses(
+        (status = CREATED, body = WebhookResponse),
+        (status = BAD_REQUEST, description = "Invalid URL or events", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "User not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn add_webhook(
+    State(state): State<AppState>,
+    Json(body): Json<PostWebhook>,
+) -> Result<(StatusCode, Json<WebhookResponse>), ApiError> {
+    let url = WebhookUrl::new(&body.url)?;
+    let events: Vec<WebhookEvent> = match &body.events {
+        Some(names) => names
+            .iter()
+            .map(|name| WebhookEvent::parse(name))
+            .collect::<Result<_, _>>()?,
+        None => WebhookEvent::ALL.to_vec(),
+    };
+    let secret = body
+        .secret
+        .unwrap_or_else(|| hex::encode(rand::random::<[u8; 32]>()));
+
+    let user_id = match body.user_id {
+        Some(user_id) => Some(
+            state
+                .repository
+                .find_user(&UserId::new(user_id))
+                .await?
+                .ok_or(ApiError::UserNotFound)?
+                .id,
+        ),
+        None => None,
+    };
+
+    let webhook_id = state
+        .repository
+        .insert_webhook(user_id.as_ref(), &url, &secret, &events, &Utc::now())
+        .await?;
+
+    let mut webhook: WebhookResponse = find_webhook(&state, webhook_id.into()).await?.into();
+    webhook.secret = Some(secret);
+
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, body = WebhookResponse),
+        (status = NOT_FOUND, description = "Webhook not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<WebhookResponse>, ApiError> {
+    Ok(Json(find_webhook(&state, id).await?.into()))
+}
+
+/// Deletes the webhook together with its delivery log.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(("id" = i64, Path)),
+    responses(
+        (status = NO_CONTENT),
+        (status = NOT_FOUND, description = "Webhook not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    state.repository.delete_webhook(&WebhookId::new(id)).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Log of the attempts to deliver events to the webhook, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks/{id}/deliveries",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(
+        ("id" = i64, Path),
+        ("limit" = Option<u32>, Query, minimum = 1, maximum = 1000, description = "50 by default"),
+    ),
+    responses(
+        (status = OK, body = Vec<DeliveryResponse>),
+        (status = BAD_REQUEST, description = "Invalid limit", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = NOT_FOUND, description = "Webhook not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn get_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Vec<DeliveryResponse>>, ApiError> {
+    let limit = match params.get("limit") {
+        Some(limit) => limit
+            .parse::<u32>()
+            .ok()
+            .filter(|limit| (1..=MAX_DELIVERIES_LIMIT).contains(limit))
+            .ok_or(ApiError::InvalidPageLimit)?,
+        None => DEFAULT_DELIVERIES_LIMIT,
+    };
+
+    let webhook = find_webhook(&state, id).await?;
+
+    let deliveries = state
+        .repository
+        .find_deliveries(&webhook.webhook_id, limit)
+        .await?
+        .into_iter()
+        .map(DeliveryResponse::from)
+        .collect();
+
+    Ok(Json(deliveries))
+}
+
+/// Posts a `ping` event to the webhook right away, once, and tells how it went.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/webhooks/{id}/test",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(("id" = i64, Path)),
+    responses(
+        (status = OK, description = "The delivery, successful or not", body = DeliveryResponse),
+        (status = NOT_FOUND, description = "Webhook not found", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = UNAUTHORIZED, description = "Missing or wrong admin token", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+        (status = FORBIDDEN, description = "No admin token is configured", body = Problem, content_type = PROBLEM_MEDIA_TYPE),
+    )
+)]
+async fn test_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<DeliveryResponse>, ApiError> {
+    let webhook = find_webhook(&state, id).await?;
+
+    Ok(Json(state.webhooks.ping(&webhook).await.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::header::AUTHORIZATION;
+
+    use super::*;
+
+    async fn serve(admin_token: Option<&str>) -> String {
+        let state = AppState::for_tests("sqlite::memory:", admin_token).await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/admin/webhooks", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, webhooks(state)).await });
+
+        url
+    }
+
+    async fn post_webhook(url: &str, token: Option<&str>) -> StatusCode {
+        let mut request = reqwest::Client::new()
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(r#"{"url":"http://127.0.0.1:1/hook"}"#);
+        if let Some(token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        request.send().await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn webhooks_cannot_be_managed_without_an_admin_token_configured() {
+        let url = serve(None).await;
+
+        assert_eq!(post_webhook(&url, None).await, StatusCode::FORBIDDEN);
+        assert_eq!(post_webhook(&url, Some("")).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            reqwest::get(&url).await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn webhooks_are_managed_with_the_admin_token() {
+        let url = serve(Some("secret")).await;
+
+        assert_eq!(post_webhook(&url, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            post_webhook(&url, Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(&url, Some("secret")).await,
+            StatusCode::CREATED
+        );
+    }
+}
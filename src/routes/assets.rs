@@ -0,0 +1,64 @@
+use axum::extract::State;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, Uri};
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, get_service};
+use axum::Router;
+use tower_http::services::ServeDir;
+
+use crate::assets::Assets;
+
+/// Files requested by their hashed name never change, browsers keep them for a year.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Files requested by their plain name are checked with the server every time they are used.
+const REVALIDATE: &str = "no-cache";
+
+/// Files of the static directory, to be nested under `assets::PREFIX`, both by their hashed and
+/// their plain name.
+pub fn assets(assets: Assets) -> Router {
+    match assets.dev_dir() {
+        // Edited files are picked up the next time the page is loaded
+        Some(dir) => Router::new()
+            .fallback_service(get_service(ServeDir::new(dir)))
+            .layer(middleware::map_response(revalidate)),
+        None => Router::new().fallback_service(get(get_asset).with_state(assets)),
+    }
+}
+
+async fn revalidate(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static(REVALIDATE));
+    response
+}
+
+/// Embedded file with the hash of its content as entity tag, so that browsers revalidating the
+/// plain names get a 304 while it does not change.
+async fn get_asset(State(assets): State<Assets>, uri: Uri, headers: HeaderMap) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    let (name, cache_control) = match assets.name(path) {
+        Some(name) => (name, IMMUTABLE),
+        None => (path, REVALIDATE),
+    };
+    let Some(file) = assets.get(name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", hex::encode(file.metadata.sha256_hash()));
+    let cached = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|tags| tags.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag));
+    let headers = [(CACHE_CONTROL, cache_control.to_string()), (ETAG, etag)];
+
+    if cached {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+    (
+        headers,
+        [(CONTENT_TYPE, file.metadata.mimetype().to_string())],
+        file.data,
+    )
+        .into_response()
+}
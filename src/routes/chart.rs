@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 
 use axum::{
@@ -7,17 +7,17 @@ use axum::{
     routing::get,
     Router,
 };
-use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
 use serde_json::json;
 
 use crate::{
     app_state::AppState,
     domain::{
+        calendar,
         user::UserId,
-        weight::{Kilograms, Weight, WeightId},
+        weight::{self, Kilograms, Weight, WeightId},
     },
     error::ApiError,
-    repositories,
 };
 
 pub fn chart(state: AppState) -> Router {
@@ -33,79 +33,55 @@ async fn render_chart(
 ) -> Result<Html<String>, ApiError> {
     let user_id: UserId = UserId::new(user_id);
 
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
+    let user = state
+        .repository
+        .find_user(&user_id)
         .await?
-        .ok_or(ApiError::UserNotFound)?
-        .id;
-
-    let end_date: DateTime<FixedOffset> = match params.get("end-date") {
-        Some(d) => Local::now()
-            .timezone()
-            .from_local_datetime(
-                &NaiveDate::parse_from_str(d, "%Y-%m-%d")
-                    .map_err(|_| ApiError::InvalidDateTime)?
-                    .and_hms_milli_opt(23, 59, 59, 999)
-                    .expect("manually set time should be valid"),
-            )
-            .unwrap()
-            .into(),
-        None => Local::now()
-            .with_time(
-                NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
-                    .expect("manually set time should be valid"),
-            )
-            .unwrap()
-            .into(),
+        .ok_or(ApiError::UserNotFound)?;
+    let timezone = user.timezone_or(state.timezone);
+    let user_id = user.id;
+
+    let end_day: NaiveDate = match params.get("end-date") {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| ApiError::InvalidDateTime("end-date"))?,
+        None => Utc::now().with_timezone(&timezone).date_naive(),
     };
 
-    let start_date: DateTime<FixedOffset> = match params.get("start-date") {
-        Some(d) => Local::now()
-            .timezone()
-            .from_local_datetime(
-                &NaiveDate::parse_from_str(d, "%Y-%m-%d")
-                    .map_err(|_| ApiError::InvalidDateTime)?
-                    .and_hms_opt(0, 0, 0)
-                    .expect("manually set time should be valid"),
-            )
-            .unwrap()
-            .into(),
-        None => end_date.with_time(NaiveTime::MIN).unwrap() - Duration::days(30),
+    let start_day: NaiveDate = match params.get("start-date") {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| ApiError::InvalidDateTime("start-date"))?,
+        None => end_day - Duration::days(30),
     };
 
-    let weights: Vec<Weight> = repositories::measurements::find_weights_between_dates(
-        &state.pool,
-        &user_id,
-        &start_date,
-        &end_date,
-    )
-    .await?
-    .into_iter()
-    .collect();
-
-    let mut dates: Vec<NaiveDate> = vec![];
-    let mut weight_kilograms: Vec<Option<f64>> = vec![];
-
-    let mut current_date = start_date;
-    let mut i = 0;
-    while current_date < end_date {
-        dates.push(current_date.date_naive());
-        if i < weights.len() {
-            let weight = weights.get(i).unwrap();
-            if current_date.date_naive() == weight.measured_at.date_naive() {
-                let kilograms = &weight.kilograms;
-                weight_kilograms.push(Some(kilograms.into()));
-                i += 1;
-            } else {
-                weight_kilograms.push(None);
-            }
-        } else {
-            weight_kilograms.push(None);
-        }
-        current_date += Duration::days(1);
+    let start_date: DateTime<FixedOffset> = calendar::start_of_day(start_day, &timezone);
+    let end_date: DateTime<FixedOffset> = calendar::end_of_day(end_day, &timezone);
+
+    let weights: Vec<Weight> = state
+        .repository
+        .find_weights_between_dates(&user_id, &start_date, &end_date)
+        .await?;
+
+    // The first weight of every day of the user, wherever it was measured
+    let mut kilograms_by_day: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for weight in &weights {
+        kilograms_by_day
+            .entry(calendar::day(&weight.measured_at, &timezone))
+            .or_insert_with(|| (&weight.kilograms).into());
     }
 
-    let duplicate_weights =
-        repositories::measurements::find_duplicate_weights(&state.pool, &user_id).await?;
+    let dates: Vec<NaiveDate> = start_day
+        .iter_days()
+        .take_while(|day| *day <= end_day)
+        .collect();
+    let weight_kilograms: Vec<Option<f64>> = dates
+        .iter()
+        .map(|day| kilograms_by_day.get(day).copied())
+        .collect();
+
+    let duplicate_weights = calendar::duplicate_days(
+        &state.repository.find_weight_timestamps(&user_id).await?,
+        &timezone,
+    );
     let mut alert_message = "".to_string();
     if !duplicate_weights.is_empty() {
         alert_message = format!(
@@ -135,22 +111,22 @@ async fn render_chart(
         .unwrap_or(&Weight {
             weight_id: WeightId::new(0),
             user_id: UserId::new(0),
-            measured_at: Local::now().into(),
+            measured_at: Utc::now().into(),
             kilograms: Kilograms::new(0.0).expect("Weight with value 0.0 must be valid"),
         })
         .kilograms
         .clone()
         .into();
 
-    let slope: f64 = calculate_slope(weights);
+    let slope: f64 = weight::slope(&weights, &timezone);
     let trend_emoji: &str = if slope > 0.0 { "↗️" } else { "↘️" };
 
     let user_id: i64 = user_id.into();
     let data = json!({
         "title": "Chart",
         "user_id": user_id,
-        "start_date": start_date.date_naive(),
-        "end_date": end_date.date_naive(),
+        "start_date": start_day,
+        "end_date": end_day,
         "dates": serde_json::to_string(&dates).map_err(|e| ApiError::Unexpected(Box::new(e)))?,
         "weights": serde_json::to_string(&weight_kilograms).map_err(|e| ApiError::Unexpected(Box::new(e)))?,
         "alert_message": alert_message,
@@ -167,25 +143,3 @@ async fn render_chart(
 
     Ok(Html(template))
 }
-
-fn calculate_slope(weights: Vec<Weight>) -> f64 {
-    let n = weights.len() as f64;
-
-    let weight_kilograms: Vec<f64> = weights.iter().map(|w| w.kilograms.clone().into()).collect();
-
-    let timestamps: Vec<f64> = weights
-        .iter()
-        .map(|w| w.measured_at.num_days_from_ce() as f64)
-        .collect();
-
-    let sum_x: f64 = timestamps.iter().sum();
-    let sum_x_square: f64 = timestamps.iter().map(|v| v * v).sum();
-    let sum_y: f64 = weight_kilograms.iter().sum();
-    let mut sum_xy: f64 = 0.0;
-
-    for i in 0..weights.len() {
-        sum_xy += weight_kilograms[i] * timestamps[i];
-    }
-
-    (n * sum_xy - sum_x * sum_y) / (n * sum_x_square - (sum_x * sum_x))
-}
@@ -0,0 +1,62 @@
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use serde_json::{json, Value};
+
+use crate::{app_state::AppState, error::ApiError, templates};
+
+pub fn health(state: AppState) -> Router {
+    Router::new()
+        .route("/healthz", get(get_health))
+        .route("/readyz", get(get_readiness))
+        .route("/version", get(get_version))
+        .with_state(state)
+}
+
+async fn get_health() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+async fn get_readiness(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let database = state.repository.ping().await.is_ok();
+
+    let migrations = state
+        .repository
+        .find_pending_migrations()
+        .await
+        .is_ok_and(|pending| pending.is_empty());
+
+    let templates = templates::are_registered(&state.handlebars);
+
+    let ready = database && migrations && templates;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if ready { "ok" } else { "unavailable" },
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+                "templates": templates,
+            }
+        })),
+    )
+}
+
+async fn get_version(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let schema_version = state
+        .repository
+        .find_applied_migrations()
+        .await?
+        .last()
+        .copied();
+
+    Ok(Json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": env!("GIT_SHA"),
+        "schema_version": schema_version,
+    })))
+}
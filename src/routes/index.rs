@@ -2,7 +2,7 @@ use axum::{extract::State, response::Html, routing::get, Router};
 use serde::Serialize;
 use serde_json::json;
 
-use crate::{app_state::AppState, error::ApiError, repositories};
+use crate::{app_state::AppState, error::ApiError};
 
 pub fn index(state: AppState) -> Router {
     Router::new()
@@ -17,7 +17,9 @@ async fn render_index(State(state): State<AppState>) -> Result<Html<String>, Api
         name: String,
     }
 
-    let users: Vec<UserResponse> = repositories::users::find_users(&state.pool)
+    let users: Vec<UserResponse> = state
+        .repository
+        .find_users()
         .await?
         .into_iter()
         .map(|u| UserResponse {
@@ -0,0 +1,100 @@
+use std::fmt::Write;
+
+use axum::{
+    extract::State,
+    http::{header::CONTENT_TYPE, Response, StatusCode},
+    routing::get,
+    Router,
+};
+
+use crate::{app_state::AppState, error::ApiError, statistics};
+
+pub fn metrics(state: AppState) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(state)
+}
+
+async fn render_metrics(State(state): State<AppState>) -> Result<Response<String>, ApiError> {
+    let mut body = state.metrics.render();
+    if state.user_gauges {
+        body.push_str(&render_user_gauges(&state).await?);
+    }
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(body)
+        .map_err(|e| ApiError::Unexpected(Box::new(e)))
+}
+
+/// Latest weight and trend of every user, rendered on each scrape instead of kept in the recorder
+/// so that the series of deleted and renamed users go away.
+async fn render_user_gauges(state: &AppState) -> Result<String, ApiError> {
+    let users = state.repository.find_users().await?;
+
+    let mut weights = String::new();
+    let mut trends = String::new();
+    for user in users {
+        let Some(reading) =
+            statistics::latest_reading(&*state.repository, &user, state.timezone).await?
+        else {
+            continue;
+        };
+
+        let user_id: i64 = (&user.id).into();
+        let user_name: String = user.name.into();
+        let labels = format!(
+            "user_id=\"{}\",user=\"{}\"",
+            user_id,
+            escape_label_value(&user_name)
+        );
+
+        let _ = writeln!(
+            weights,
+            "user_weight_kilograms{{{}}} {}",
+            labels,
+            f64::from(reading.weight.kilograms)
+        );
+        if let Some(trend) = reading.trend {
+            let _ = writeln!(
+                trends,
+                "user_weight_trend_kilograms_per_day{{{}}} {}",
+                labels, trend
+            );
+        }
+    }
+
+    let mut gauges = String::new();
+    for (name, samples) in [
+        ("user_weight_kilograms", weights),
+        ("user_weight_trend_kilograms_per_day", trends),
+    ] {
+        if !samples.is_empty() {
+            let _ = write!(gauges, "# TYPE {} gauge\n{}\n", name, samples);
+        }
+    }
+
+    Ok(gauges)
+}
+
+/// Backslashes, double quotes and line feeds are escaped in the label values of the text format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(
+            escape_label_value("Ana \"Nani\" C:\\\nB"),
+            "Ana \\\"Nani\\\" C:\\\\\\nB"
+        );
+    }
+}
@@ -6,15 +6,13 @@ use axum::{
     routing::get,
     Router,
 };
-use chrono::{DateTime, Local};
 use serde::Serialize;
 use serde_json::json;
 
 use crate::{
     app_state::AppState,
-    domain::{user::UserId, weight::Weight},
+    domain::{calendar, user::UserId, weight::Weight},
     error::ApiError,
-    repositories,
 };
 
 pub fn table(state: AppState) -> Router {
@@ -30,10 +28,14 @@ async fn render_table(
 ) -> Result<Html<String>, ApiError> {
     let user_id = UserId::new(user_id);
 
-    let user_id = repositories::users::find_user(&state.pool, &user_id)
+    let user = state
+        .repository
+        .find_user(&user_id)
         .await?
-        .ok_or(ApiError::UserNotFound)?
-        .id;
+        .ok_or(ApiError::UserNotFound)?;
+    let timezone = user.timezone_or(state.timezone);
+    let user_id = user.id;
+    let timestamps = state.repository.find_weight_timestamps(&user_id).await?;
 
     #[derive(Serialize)]
     struct WeightsResponse {
@@ -42,31 +44,47 @@ async fn render_table(
         kilograms: f64,
     }
 
-    let years = repositories::measurements::find_years(&state.pool, &user_id).await?;
+    let years = calendar::years(&timestamps, &timezone);
     let year: &str = params
         .get("year")
         .map(String::as_str)
         .unwrap_or(years.first().map(String::as_str).unwrap_or_default());
 
-    let months =
-        repositories::measurements::find_months_by_year(&state.pool, &user_id, year).await?;
+    let months = year
+        .parse()
+        .map(|year| calendar::months(&timestamps, &timezone, year))
+        .unwrap_or_default();
     let month: &str = params
         .get("month")
         .map(String::as_str)
         .unwrap_or(months.first().map(String::as_str).unwrap_or_default());
 
-    let weights: Vec<WeightsResponse> =
-        repositories::measurements::find_weights_by_year_month(&state.pool, &user_id, year, month)
-            .await?
-            .into_iter()
-            .map(|w: Weight| WeightsResponse {
-                weight_id: w.weight_id.into(),
-                measured_at: DateTime::<Local>::from(w.measured_at)
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string(),
-                kilograms: w.kilograms.into(),
-            })
-            .collect();
+    let weights: Vec<Weight> = match (year.parse(), month.parse()) {
+        (Ok(year), Ok(month)) => match calendar::month_bounds(year, month, &timezone) {
+            Some((start_date, end_date)) => {
+                state
+                    .repository
+                    .find_weights_between_dates(&user_id, &start_date, &end_date)
+                    .await?
+            }
+            None => vec![],
+        },
+        _ => vec![],
+    };
+
+    let weights: Vec<WeightsResponse> = weights
+        .into_iter()
+        .rev()
+        .map(|w: Weight| WeightsResponse {
+            weight_id: w.weight_id.into(),
+            measured_at: w
+                .measured_at
+                .with_timezone(&timezone)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            kilograms: w.kilograms.into(),
+        })
+        .collect();
 
     let user_id: i64 = user_id.into();
     let data = json!({
@@ -0,0 +1,25 @@
+use std::{future::Future, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::error::ApiError;
+
+/// Runs the task every period, the first time right away, logging its failures with the message
+/// without stopping the next runs.
+pub fn every<F, T>(period: Duration, failure: &'static str, task: F)
+where
+    F: Fn(DateTime<Utc>) -> T + Send + 'static,
+    T: Future<Output = Result<(), ApiError>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(error) = task(Utc::now()).await {
+                tracing::error!(?error, "{}", failure);
+            }
+        }
+    });
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    domain::{
+        user::User,
+        weight::{self, Weight},
+    },
+    error::ApiError,
+    repositories::Repository,
+};
+
+/// Number of days taken into account to calculate the trend of the latest reading.
+const TREND_DAYS: i64 = 30;
+
+/// Latest weight of a user together with the values derived from it.
+pub struct LatestReading {
+    pub weight: Weight,
+    pub bmi: Option<f64>,
+    /// Kilograms per day over the last days, none when there are not enough weights.
+    pub trend: Option<f64>,
+}
+
+/// `timezone` is the one of the users without their own, for the days of the trend.
+pub async fn latest_reading(
+    repository: &dyn Repository,
+    user: &User,
+    timezone: Tz,
+) -> Result<Option<LatestReading>, ApiError> {
+    let Some(latest_weight) = repository.find_latest_weight(&user.id).await? else {
+        return Ok(None);
+    };
+
+    let end_date: DateTime<FixedOffset> = Utc::now().into();
+    let start_date = end_date - Duration::days(TREND_DAYS);
+    let weights = repository
+        .find_weights_between_dates(&user.id, &start_date, &end_date)
+        .await?;
+
+    let bmi: Option<f64> = user
+        .height
+        .as_ref()
+        .map(|height| latest_weight.kilograms.body_mass_index(height));
+    let trend: Option<f64> = Some(weight::slope(&weights, &user.timezone_or(timezone)))
+        .filter(|slope| slope.is_finite());
+
+    Ok(Some(LatestReading {
+        weight: latest_weight,
+        bmi,
+        trend,
+    }))
+}
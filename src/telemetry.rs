@@ -0,0 +1,122 @@
+use std::{future::Future, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderName,
+    middleware::Next,
+    response::Response,
+};
+use metrics::{counter, histogram};
+use metrics_exporter_prometheus::{BuildError, PrometheusBuilder, PrometheusHandle};
+use tower_http::{
+    classify::{ServerErrorsAsFailures, SharedClassifier},
+    trace::{DefaultOnRequest, DefaultOnResponse, MakeSpan, TraceLayer},
+};
+use tracing::{Instrument, Level, Span};
+use tracing_subscriber::{fmt, util::TryInitError, EnvFilter};
+
+use crate::configuration::{LogFormat, LoggingConfiguration};
+
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+const DURATION_BUCKETS: [f64; 11] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+pub fn init_tracing(configuration: &LoggingConfiguration) -> Result<(), TryInitError> {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+    let filter = EnvFilter::new(&configuration.level);
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match configuration.format {
+        LogFormat::Pretty => registry
+            .with(fmt::layer().with_writer(std::io::stderr))
+            .try_init(),
+        LogFormat::Json => registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_writer(std::io::stderr),
+            )
+            .try_init(),
+    }
+}
+
+/// Tracing layer that opens a span per request carrying the id set by `SetRequestIdLayer`,
+/// so every event logged while handling the request can be correlated.
+pub fn trace_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>, RequestSpan> {
+    TraceLayer::new_for_http()
+        .make_span_with(RequestSpan)
+        .on_request(DefaultOnRequest::new().level(Level::DEBUG))
+        .on_response(DefaultOnResponse::new().level(Level::INFO))
+}
+
+#[derive(Clone)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        tracing::info_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            request_id,
+        )
+    }
+}
+
+pub fn install_recorder() -> Result<PrometheusHandle, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets(&DURATION_BUCKETS)?
+        .install_recorder()
+}
+
+/// Middleware that counts the requests and measures their latency per route.
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or("unmatched".to_string());
+    let method = request.method().to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let status = response.status().as_u16().to_string();
+    counter!("http_requests_total", "method" => method.clone(), "route" => route.clone(), "status" => status)
+        .increment(1);
+    histogram!("http_request_duration_seconds", "method" => method, "route" => route)
+        .record(elapsed);
+
+    response
+}
+
+/// Awaits a database query inside its own span recording how long it took.
+pub async fn time_query<F: Future>(query: &'static str, future: F) -> F::Output {
+    let start = Instant::now();
+    let output = future
+        .instrument(tracing::debug_span!("db_query", query))
+        .await;
+    record_query_duration(query, start);
+    output
+}
+
+/// Records how long a query took, for streamed queries once all of their rows have been read.
+pub fn record_query_duration(query: &'static str, start: Instant) {
+    histogram!("db_query_duration_seconds", "query" => query).record(start.elapsed().as_secs_f64());
+}
+
+pub fn record_ingestion(measurement: &'static str, source: &'static str) {
+    counter!("measurements_ingested_total", "measurement" => measurement, "source" => source)
+        .increment(1);
+}
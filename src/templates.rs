@@ -1,17 +1,54 @@
-use handlebars::TemplateError;
+use std::{io, path::Path};
 
-pub fn register(handlebars: &mut handlebars::Handlebars) -> Result<(), TemplateError> {
-    handlebars.register_template_file("base", "./templates/base.hbs")?;
+use handlebars::{TemplateError, TemplateErrorReason};
+use rust_embed::RustEmbed;
 
-    handlebars.register_template_file("navigation", "./templates/navigation.hbs")?;
+/// Templates of the pages and emails, built into the binary so that it runs from any directory.
+#[derive(RustEmbed)]
+#[folder = "templates"]
+struct Templates;
 
-    handlebars.register_template_file("index", "./templates/index.hbs")?;
+const TEMPLATES: [(&str, &str); 7] = [
+    ("base", "base.hbs"),
+    ("navigation", "navigation.hbs"),
+    ("index", "index.hbs"),
+    ("chart", "chart.hbs"),
+    ("table", "table.hbs"),
+    ("not_found", "404.hbs"),
+    ("digest", "digest.hbs"),
+];
 
-    handlebars.register_template_file("chart", "./templates/chart.hbs")?;
+/// Registers the templates embedded in the binary, or in dev mode the ones of the directory, which
+/// are read again whenever they are rendered.
+pub fn register(
+    handlebars: &mut handlebars::Handlebars,
+    dev_dir: Option<&Path>,
+) -> Result<(), TemplateError> {
+    if let Some(dir) = dev_dir {
+        handlebars.set_dev_mode(true);
+        for (name, file) in TEMPLATES {
+            handlebars.register_template_file(name, dir.join(file))?;
+        }
+        return Ok(());
+    }
 
-    handlebars.register_template_file("table", "./templates/table.hbs")?;
-
-    handlebars.register_template_file("not_found", "./templates/404.hbs")?;
+    for (name, file) in TEMPLATES {
+        let template = Templates::get(file).ok_or_else(|| {
+            TemplateError::of(TemplateErrorReason::IoError(
+                io::ErrorKind::NotFound.into(),
+                file.to_string(),
+            ))
+        })?;
+        let source = String::from_utf8_lossy(&template.data);
+        handlebars.register_template_string(name, source)?;
+    }
 
     Ok(())
 }
+
+/// Whether every template needed by the pages has been registered.
+pub fn are_registered(handlebars: &handlebars::Handlebars) -> bool {
+    TEMPLATES
+        .iter()
+        .all(|(name, _)| handlebars.has_template(name))
+}
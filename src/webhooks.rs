@@ -0,0 +1,551 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use futures::{future, StreamExt};
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{
+    configuration::WebhooksConfiguration,
+    domain::webhook::{Delivery, Webhook, WebhookEvent},
+    error::ApiError,
+    events::{Change, Event, Events, Notification},
+    repositories::Repository,
+};
+
+/// Name of the event of the test deliveries, sent whatever the webhook is subscribed to.
+pub const PING: &str = "ping";
+
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// `sha256=` followed by the hex encoded HMAC-SHA256, keyed with the secret of the webhook, of the
+/// timestamp header, a dot and the body.
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Posts the events to the webhooks subscribed to them and logs every attempt.
+#[derive(Clone)]
+pub struct Webhooks {
+    repository: Arc<dyn Repository>,
+    client: reqwest::Client,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl Webhooks {
+    pub fn new(
+        repository: Arc<dyn Repository>,
+        configuration: &WebhooksConfiguration,
+    ) -> Result<Self, ApiError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(configuration.timeout_seconds))
+            .user_agent(concat!("weight-tracker/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| ApiError::Unexpected(Box::new(e)))?;
+
+        Ok(Webhooks {
+            repository,
+            client,
+            max_attempts: configuration.max_attempts,
+            retry_delay: Duration::from_secs(configuration.retry_delay_seconds),
+        })
+    }
+
+    /// Delivers the events published from now on, until the events are closed.
+    pub fn dispatch(&self, events: &Events) {
+        let webhooks = self.clone();
+        let mut notifications = events.subscribe_all();
+
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.next().await {
+                match notification {
+                    Notification::Event(event) => webhooks.publish(&event).await,
+                    Notification::Lagged(missed) => {
+                        tracing::warn!(missed, "Webhooks fell behind, events were not delivered");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Carries on in the background with the retries the server was waiting for when it stopped.
+    pub fn resume(&self) {
+        let webhooks = self.clone();
+
+        tokio::spawn(async move {
+            let deliveries = match webhooks
+                .repository
+                .find_pending_deliveries(webhooks.max_attempts)
+                .await
+            {
+                Ok(deliveries) => deliveries,
+                Err(error) => {
+                    tracing::error!(?error, "Failed to read the pending webhook deliveries");
+                    return;
+                }
+            };
+
+            // Pings are never retried
+            for delivery in deliveries
+                .into_iter()
+                .filter(|delivery| delivery.event != PING)
+            {
+                let webhook = match webhooks.repository.find_webhook(&delivery.webhook_id).await {
+                    Ok(Some(webhook)) => webhook,
+                    Ok(None) => continue,
+                    Err(error) => {
+                        tracing::error!(?error, "Failed to read the webhook");
+                        continue;
+                    }
+                };
+
+                let webhooks = webhooks.clone();
+                tokio::spawn(async move {
+                    let (event, payload) = (delivery.event.clone(), delivery.payload.clone());
+                    webhooks.retry(&webhook, &event, &payload, delivery).await
+                });
+            }
+        });
+    }
+
+    async fn publish(&self, event: &Event) {
+        let (webhook_event, data) = describe(event);
+        self.notify(webhook_event, event.user_id(), data).await;
+    }
+
+    /// Starts delivering the event of the user to every webhook subscribed to it.
+    pub async fn notify(&self, event: WebhookEvent, user_id: i64, data: Value) {
+        let payload = payload(event.name(), data);
+        for webhook in self.subscribed(event, user_id).await {
+            let webhooks = self.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move { webhooks.deliver(&webhook, event.name(), &payload).await });
+        }
+    }
+
+    /// Makes the first attempt to deliver the event of the user to every webhook subscribed to it
+    /// and returns how many succeeded, the failed ones are retried in the background.
+    pub async fn notify_first_attempt(
+        &self,
+        event: WebhookEvent,
+        user_id: i64,
+        data: Value,
+    ) -> usize {
+        let payload = payload(event.name(), data);
+        let deliveries = self
+            .subscribed(event, user_id)
+            .await
+            .into_iter()
+            .map(|webhook| {
+                let payload = payload.clone();
+                async move {
+                    let delivery = self.attempt(&webhook, event.name(), &payload, 1).await;
+                    let succeeded = delivery.succeeded;
+                    if !succeeded {
+                        let webhooks = self.clone();
+                        tokio::spawn(async move {
+                            webhooks
+                                .retry(&webhook, event.name(), &payload, delivery)
+                                .await
+                        });
+                    }
+                    succeeded
+                }
+            });
+
+        future::join_all(deliveries)
+            .await
+            .into_iter()
+            .filter(|succeeded| *succeeded)
+            .count()
+    }
+
+    async fn subscribed(&self, event: WebhookEvent, user_id: i64) -> Vec<Webhook> {
+        match self.repository.find_webhooks().await {
+            Ok(webhooks) => webhooks
+                .into_iter()
+                .filter(|webhook| webhook.wants(event, user_id))
+                .collect(),
+            Err(error) => {
+                tracing::error!(?error, "Failed to read the webhooks");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Posts the payload until it is accepted or the attempts run out, waiting twice as long after
+    /// each failed one. The last attempt is returned.
+    pub async fn deliver(&self, webhook: &Webhook, event: &str, payload: &str) -> Delivery {
+        let delivery = self.attempt(webhook, event, payload, 1).await;
+        self.retry(webhook, event, payload, delivery).await
+    }
+
+    /// Carries on from the last failed attempt of a delivery, the last attempt is returned.
+    async fn retry(
+        &self,
+        webhook: &Webhook,
+        event: &str,
+        payload: &str,
+        mut delivery: Delivery,
+    ) -> Delivery {
+        let mut delay = self
+            .retry_delay
+            .saturating_mul(2u32.saturating_pow(delivery.attempt.saturating_sub(1)));
+
+        while !delivery.succeeded && delivery.attempt < self.max_attempts {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            delivery = self
+                .attempt(webhook, event, payload, delivery.attempt + 1)
+                .await;
+        }
+
+        if !delivery.succeeded {
+            tracing::warn!(
+                webhook_id = i64::from(&webhook.webhook_id),
+                event,
+                attempts = delivery.attempt,
+                "Webhook delivery abandoned"
+            );
+        }
+        delivery
+    }
+
+    /// Posts a `ping` to the webhook once, without retrying it.
+    pub async fn ping(&self, webhook: &Webhook) -> Delivery {
+        let data = json!({ "webhook_id": i64::from(&webhook.webhook_id) });
+        self.attempt(webhook, PING, &payload(PING, data), 1).await
+    }
+
+    async fn attempt(
+        &self,
+        webhook: &Webhook,
+        event: &str,
+        payload: &str,
+        attempt: u32,
+    ) -> Delivery {
+        let attempted_at = Utc::now();
+        let timestamp = attempted_at.timestamp();
+
+        let response = self
+            .client
+            .post(webhook.url.as_url().clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(
+                SIGNATURE_HEADER,
+                signature(&webhook.secret, timestamp, payload),
+            )
+            .body(payload.to_string())
+            .send()
+            .await;
+
+        let (status_code, error) = match response {
+            Ok(response) => (Some(response.status()), None),
+            Err(error) => (error.status(), Some(error.to_string())),
+        };
+
+        let delivery = Delivery {
+            webhook_id: webhook.webhook_id,
+            event: event.to_string(),
+            payload: payload.to_string(),
+            attempt,
+            attempted_at,
+            status_code: status_code.map(|status| status.as_u16()),
+            error,
+            succeeded: status_code.is_some_and(|status| status.is_success()),
+        };
+
+        if let Err(error) = self.repository.insert_delivery(&delivery).await {
+            tracing::error!(?error, "Failed to log the webhook delivery");
+        }
+
+        delivery
+    }
+}
+
+/// Value of the signature header of a delivery.
+pub fn signature(secret: &str, timestamp: i64, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn describe(event: &Event) -> (WebhookEvent, Value) {
+    match event {
+        Event::Measurement(measurement) => match measurement.change {
+            Change::Inserted => (WebhookEvent::MeasurementCreated, json!(measurement)),
+            Change::Deleted => (WebhookEvent::MeasurementDeleted, json!(measurement)),
+        },
+        Event::GoalReached(goal) => (WebhookEvent::GoalReached, json!(goal)),
+    }
+}
+
+fn payload(event: &str, data: Value) -> String {
+    json!({
+        "event": event,
+        "occurred_at": Utc::now(),
+        "data": data,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use chrono::DateTime;
+
+    use crate::{
+        configuration::DatabaseConfiguration,
+        domain::webhook::WebhookUrl,
+        events::{GoalEvent, MeasurementEvent},
+        repositories::{schema::SchemaRepository, sqlite::SqliteRepository},
+    };
+
+    use super::*;
+
+    /// Stand-in for the receiving end of a webhook, answering with the statuses in order and then
+    /// with 200.
+    #[derive(Clone, Default)]
+    struct Receiver {
+        statuses: Arc<Mutex<Vec<StatusCode>>>,
+        received: Arc<Mutex<Vec<(HeaderMap, String)>>>,
+    }
+
+    impl Receiver {
+        async fn serve(statuses: Vec<StatusCode>) -> (Receiver, WebhookUrl) {
+            let receiver = Receiver {
+                statuses: Arc::new(Mutex::new(statuses.into_iter().rev().collect())),
+                ..Default::default()
+            };
+            let router = Router::new()
+                .route("/hook", post(receive))
+                .with_state(receiver.clone());
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            (receiver, WebhookUrl::new(&url).unwrap())
+        }
+
+        fn received(&self) -> Vec<(HeaderMap, String)> {
+            self.received.lock().unwrap().clone()
+        }
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        receiver.received.lock().unwrap().push((headers, body));
+        receiver
+            .statuses
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or(StatusCode::OK)
+    }
+
+    async fn webhooks(max_attempts: u32) -> Webhooks {
+        let repository = SqliteRepository::connect(&DatabaseConfiguration {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+        })
+        .await
+        .unwrap();
+        repository.run_migrations().await.unwrap();
+
+        Webhooks::new(
+            Arc::new(repository),
+            &WebhooksConfiguration {
+                max_attempts,
+                retry_delay_seconds: 0,
+                timeout_seconds: 5,
+            },
+        )
+        .unwrap()
+    }
+
+    async fn register(webhooks: &Webhooks, url: &WebhookUrl, events: &[WebhookEvent]) -> Webhook {
+        let repository = &webhooks.repository;
+        let webhook_id = repository
+            .insert_webhook(None, url, "secret", events, &Utc::now())
+            .await
+            .unwrap();
+        repository.find_webhook(&webhook_id).await.unwrap().unwrap()
+    }
+
+    fn weight(user_id: i64, id: i64) -> MeasurementEvent {
+        MeasurementEvent {
+            user_id,
+            measurement: "weight",
+            id,
+            change: Change::Inserted,
+            measured_at: DateTime::parse_from_rfc3339("2026-10-19T08:00:00+02:00").unwrap(),
+            value: 79.8,
+            unit: "kg",
+        }
+    }
+
+    #[test]
+    fn signature_is_the_hmac_of_the_timestamp_and_the_payload() {
+        assert_eq!(
+            signature("secret", 1760853600, r#"{"event":"ping"}"#),
+            "sha256=fbc2266aef7fabe01efe4a3b2ca276a05eaa4a0785634d0e3db3ea12aceb0a49"
+        );
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_signed() {
+        let webhooks = webhooks(1).await;
+        let (receiver, url) = Receiver::serve(vec![]).await;
+        let webhook = register(&webhooks, &url, &[]).await;
+
+        let delivery = webhooks.ping(&webhook).await;
+
+        assert!(delivery.succeeded);
+        let (headers, body) = receiver.received().remove(0);
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[EVENT_HEADER], PING);
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            signature("secret", timestamp, &body)
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_retried_and_logged() {
+        let webhooks = webhooks(3).await;
+        let (receiver, url) = Receiver::serve(vec![
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ])
+        .await;
+        let webhook = register(&webhooks, &url, &[WebhookEvent::MeasurementCreated]).await;
+
+        let delivery = webhooks
+            .deliver(&webhook, "measurement.created", "{}")
+            .await;
+
+        assert!(delivery.succeeded);
+        assert_eq!(delivery.attempt, 3);
+        assert_eq!(receiver.received().len(), 3);
+        let logged: Vec<(u32, Option<u16>)> = webhooks
+            .repository
+            .find_deliveries(&webhook.webhook_id, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|delivery| (delivery.attempt, delivery.status_code))
+            .collect();
+        assert_eq!(logged, vec![(3, Some(200)), (2, Some(503)), (1, Some(500))]);
+    }
+
+    #[tokio::test]
+    async fn deliveries_stop_when_the_attempts_run_out() {
+        let webhooks = webhooks(2).await;
+        let (receiver, url) =
+            Receiver::serve(vec![StatusCode::BAD_GATEWAY, StatusCode::BAD_GATEWAY]).await;
+        let webhook = register(&webhooks, &url, &[]).await;
+
+        let delivery = webhooks.deliver(&webhook, PING, "{}").await;
+
+        assert!(!delivery.succeeded);
+        assert_eq!(receiver.received().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn pending_deliveries_are_resumed() {
+        let webhooks = webhooks(3).await;
+        let (receiver, url) = Receiver::serve(vec![]).await;
+        let webhook = register(&webhooks, &url, &[WebhookEvent::MeasurementCreated]).await;
+        let logged = |event: &str, payload: &str, attempt: u32, succeeded: bool| Delivery {
+            webhook_id: webhook.webhook_id,
+            event: event.to_string(),
+            payload: payload.to_string(),
+            attempt,
+            attempted_at: Utc::now(),
+            status_code: None,
+            error: None,
+            succeeded,
+        };
+        for delivery in [
+            logged("measurement.created", "pending", 1, false),
+            logged("measurement.created", "abandoned", 3, false),
+            logged("measurement.created", "delivered", 1, false),
+            logged("measurement.created", "delivered", 2, true),
+            logged(PING, "ping", 1, false),
+        ] {
+            webhooks
+                .repository
+                .insert_delivery(&delivery)
+                .await
+                .unwrap();
+        }
+
+        webhooks.resume();
+
+        for _ in 0..50 {
+            if !receiver.received().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let received: Vec<String> = receiver
+            .received()
+            .into_iter()
+            .map(|(_, body)| body)
+            .collect();
+        assert_eq!(received, vec!["pending"]);
+        let latest = webhooks
+            .repository
+            .find_deliveries(&webhook.webhook_id, 1)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!((latest.attempt, latest.succeeded), (2, true));
+    }
+
+    #[tokio::test]
+    async fn events_are_sent_to_the_webhooks_subscribed_to_them() {
+        let webhooks = webhooks(1).await;
+        let events = Events::new();
+        let (goals, goals_url) = Receiver::serve(vec![]).await;
+        let (weights, weights_url) = Receiver::serve(vec![]).await;
+        register(&webhooks, &goals_url, &[WebhookEvent::GoalReached]).await;
+        register(&webhooks, &weights_url, &[WebhookEvent::MeasurementCreated]).await;
+        webhooks.dispatch(&events);
+
+        events.publish(weight(1, 7));
+        events.publish(GoalEvent {
+            user_id: 1,
+            goal_kilograms: 80.0,
+            weight_id: 7,
+            measured_at: weight(1, 7).measured_at,
+            kilograms: 79.8,
+        });
+
+        for _ in 0..50 {
+            if !goals.received().is_empty() && !weights.received().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let body = |receiver: &Receiver| -> Value {
+            serde_json::from_str(&receiver.received()[0].1).unwrap()
+        };
+        assert_eq!(weights.received().len(), 1);
+        assert_eq!(body(&weights)["event"], "measurement.created");
+        assert_eq!(body(&weights)["data"]["value"], 79.8);
+        assert_eq!(goals.received().len(), 1);
+        assert_eq!(body(&goals)["event"], "goal.reached");
+    }
+}
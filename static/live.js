@@ -0,0 +1,17 @@
+// Reloads the page when a weight of the user is added or deleted, so it never shows stale data
+(() => {
+  const userId = document.currentScript.getAttribute('data-js-user-id');
+  const events = new EventSource('/api/v1/users/' + userId + '/events');
+
+  const reload = _ => {
+    events.close();
+    window.location.reload();
+  };
+
+  events.addEventListener('measurement', event => {
+    if (JSON.parse(event.data).measurement === 'weight') {
+      reload();
+    }
+  });
+  events.addEventListener('lagged', reload);
+})();